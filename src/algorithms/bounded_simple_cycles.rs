@@ -0,0 +1,309 @@
+use std::{
+    collections::VecDeque,
+    hash::Hash,
+    time::{Duration, Instant},
+};
+
+use petgraph::{
+    algo::kosaraju_scc,
+    visit::{IntoNeighborsDirected, IntoNodeIdentifiers, NodeIndexable, Visitable},
+    Direction,
+};
+
+use crate::hash::{HashMap, HashSet};
+
+/// How often the deadline is checked, in search steps.
+const DEADLINE_CHECK_INTERVAL: usize = 1024;
+
+/// Limits on the enumeration of simple cycles. `None` means unlimited.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CycleLimits {
+    /// Longest cycle to look for, in edges.
+    pub max_length: Option<usize>,
+    pub max_count: Option<usize>,
+    /// Wall-clock time after which the enumeration stops.
+    pub timeout: Option<Duration>,
+}
+
+impl CycleLimits {
+    /// Takes the limits that aren't set from `defaults`.
+    pub fn or(self, defaults: CycleLimits) -> Self {
+        Self {
+            max_length: self.max_length.or(defaults.max_length),
+            max_count: self.max_count.or(defaults.max_count),
+            timeout: self.timeout.or(defaults.timeout),
+        }
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.max_length.is_none() && self.max_count.is_none() && self.timeout.is_none()
+    }
+}
+
+/// Whether all the simple cycles were found, or which limit stopped the enumeration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Completeness {
+    Complete,
+    /// A strongly connected component is larger than the maximum length, so longer cycles may
+    /// have been skipped.
+    MaxLength,
+    /// More cycles than the maximum count exist.
+    MaxCount,
+    Timeout,
+}
+
+pub struct BoundedCycles<N> {
+    /// Cycles as the sequence of nodes that form them, from the shortest to the longest.
+    pub cycles: Vec<Vec<N>>,
+    pub completeness: Completeness,
+}
+
+struct Search<G: IntoNeighborsDirected, F> {
+    graph: G,
+    keep: F,
+    component_of: Vec<usize>,
+    max_count: Option<usize>,
+    deadline: Option<Instant>,
+    steps: usize,
+    distances: HashMap<G::NodeId, usize>,
+    queue: VecDeque<G::NodeId>,
+    on_path: HashSet<G::NodeId>,
+    cycles: Vec<Vec<G::NodeId>>,
+}
+
+impl<G, F> Search<G, F>
+where
+    G: IntoNeighborsDirected + NodeIndexable,
+    G::NodeId: Eq + Hash,
+    F: FnMut(&[G::NodeId]) -> bool,
+{
+    /// Finds the cycles of exactly `length` edges whose node with the smallest index is `start`,
+    /// so that each cycle is found once.
+    fn find_cycles_of_length(
+        &mut self,
+        start: G::NodeId,
+        length: usize,
+    ) -> Result<(), Completeness> {
+        let graph = self.graph;
+        let start_index = graph.to_index(start);
+        let component = self.component_of[start_index];
+        let is_candidate = |node: G::NodeId| {
+            let index = graph.to_index(node);
+            index > start_index && self.component_of[index] == component
+        };
+
+        // Distances back to `start`, used to prune the paths that can't close in time
+        self.distances.clear();
+        self.distances.insert(start, 0);
+        self.queue.push_back(start);
+        while let Some(node) = self.queue.pop_front() {
+            let distance = self.distances[&node];
+            if distance + 1 >= length {
+                continue;
+            }
+            for predecessor in graph.neighbors_directed(node, Direction::Incoming) {
+                if is_candidate(predecessor) && !self.distances.contains_key(&predecessor) {
+                    self.distances.insert(predecessor, distance + 1);
+                    self.queue.push_back(predecessor);
+                }
+            }
+        }
+
+        let mut path = vec![start];
+        let mut stack = vec![graph.neighbors_directed(start, Direction::Outgoing)];
+        self.on_path.clear();
+        while let Some(neighbors) = stack.last_mut() {
+            self.steps += 1;
+            if self.steps.is_multiple_of(DEADLINE_CHECK_INTERVAL)
+                && self
+                    .deadline
+                    .is_some_and(|deadline| Instant::now() >= deadline)
+            {
+                return Err(Completeness::Timeout);
+            }
+            let Some(next) = neighbors.next() else {
+                stack.pop();
+                self.on_path.remove(&path.pop().unwrap());
+                continue;
+            };
+            // Number of edges of the path once extended to `next`
+            let edge_count = path.len();
+            if next == start {
+                if edge_count == length && (self.keep)(&path) {
+                    if Some(self.cycles.len()) == self.max_count {
+                        return Err(Completeness::MaxCount);
+                    }
+                    self.cycles.push(path.clone());
+                }
+                continue;
+            }
+            let Some(distance) = self.distances.get(&next) else {
+                continue;
+            };
+            if edge_count + distance <= length && self.on_path.insert(next) {
+                path.push(next);
+                stack.push(graph.neighbors_directed(next, Direction::Outgoing));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Enumerates the simple cycles of `graph` from the shortest to the longest, stopping at the
+/// `limits`. Cycles of the same length are ordered by the index of their first node, which is
+/// the one with the smallest index. Only the cycles for which `keep` returns true are listed and
+/// counted towards the maximum count.
+///
+/// Each length is searched separately with a depth-limited DFS, so unlike Johnson's algorithm
+/// ([`find_simple_cycles`](super::johnson_simple_cycles::find_simple_cycles)) the short cycles
+/// of a dense component are found without going through the long ones. Parallel edges yield the
+/// same cycle several times.
+pub fn find_bounded_simple_cycles<G>(
+    graph: G,
+    limits: &CycleLimits,
+    keep: impl FnMut(&[G::NodeId]) -> bool,
+) -> BoundedCycles<G::NodeId>
+where
+    G: IntoNeighborsDirected + IntoNodeIdentifiers + NodeIndexable + Visitable,
+    G::NodeId: Eq + Hash,
+{
+    let deadline = limits.timeout.map(|timeout| Instant::now() + timeout);
+    let mut components = kosaraju_scc(graph);
+    let mut component_of = vec![usize::MAX; graph.node_bound()];
+    for (component_index, component) in components.iter_mut().enumerate() {
+        component.sort_unstable_by_key(|node| graph.to_index(*node));
+        for node in component.iter() {
+            component_of[graph.to_index(*node)] = component_index;
+        }
+    }
+    let largest_component = components.iter().map(Vec::len).max().unwrap_or(0);
+    let max_length = limits.max_length.map_or(largest_component, |max_length| {
+        max_length.min(largest_component)
+    });
+
+    let mut search = Search {
+        graph,
+        keep,
+        component_of,
+        max_count: limits.max_count,
+        deadline,
+        steps: 0,
+        distances: HashMap::default(),
+        queue: VecDeque::new(),
+        on_path: HashSet::default(),
+        cycles: vec![],
+    };
+    let result = (1..=max_length).try_for_each(|length| {
+        components
+            .iter()
+            .filter(|component| component.len() >= length)
+            .flatten()
+            .try_for_each(|start| search.find_cycles_of_length(*start, length))
+    });
+    let completeness = match result {
+        Ok(()) if max_length < largest_component => Completeness::MaxLength,
+        Ok(()) => Completeness::Complete,
+        Err(completeness) => completeness,
+    };
+    BoundedCycles {
+        cycles: search.cycles,
+        completeness,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::Graph;
+    use rustworkx_core::generators::complete_graph;
+    use test_case::test_case;
+
+    fn mesh_graph(node_count: usize) -> Graph<(), ()> {
+        complete_graph(Some(node_count), None, || (), || ()).unwrap()
+    }
+
+    #[test]
+    fn test_shortest_first() {
+        let graph = Graph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 1), (3, 3), (3, 0)]);
+        let found = find_bounded_simple_cycles(&graph, &CycleLimits::default(), |_| true);
+        assert_eq!(found.completeness, Completeness::Complete);
+        let cycles = found
+            .cycles
+            .into_iter()
+            .map(|cycle| cycle.into_iter().map(|node| node.index()).collect())
+            .collect::<Vec<Vec<usize>>>();
+        assert_eq!(cycles, vec![vec![3], vec![1, 2], vec![0, 1, 2]]);
+    }
+
+    #[test_case(2, 1)]
+    #[test_case(3, 5)]
+    #[test_case(4, 20)]
+    #[test_case(5, 84)]
+    #[test_case(6, 409)]
+    fn test_mesh_graph(node_count: usize, expected_cycle_count: usize) {
+        let found =
+            find_bounded_simple_cycles(&mesh_graph(node_count), &CycleLimits::default(), |_| true);
+        assert_eq!(found.completeness, Completeness::Complete);
+        assert_eq!(found.cycles.len(), expected_cycle_count);
+    }
+
+    #[test]
+    fn test_max_length() {
+        let limits = CycleLimits {
+            max_length: Some(3),
+            ..Default::default()
+        };
+        let found = find_bounded_simple_cycles(&mesh_graph(8), &limits, |_| true);
+        assert_eq!(found.completeness, Completeness::MaxLength);
+        // 28 pairs of nodes, and 2 cycles through each of the 56 triples
+        assert_eq!(found.cycles.len(), 28 + 56 * 2);
+        assert!(found.cycles.iter().all(|cycle| cycle.len() <= 3));
+    }
+
+    #[test]
+    fn test_max_count() {
+        let limits = CycleLimits {
+            max_count: Some(30),
+            ..Default::default()
+        };
+        let found = find_bounded_simple_cycles(&mesh_graph(8), &limits, |_| true);
+        assert_eq!(found.completeness, Completeness::MaxCount);
+        assert_eq!(found.cycles.len(), 30);
+        assert!(found.cycles[..28].iter().all(|cycle| cycle.len() == 2));
+
+        // Not truncated when there are exactly as many cycles as allowed
+        let limits = CycleLimits {
+            max_count: Some(5),
+            ..Default::default()
+        };
+        let found = find_bounded_simple_cycles(&mesh_graph(3), &limits, |_| true);
+        assert_eq!(found.completeness, Completeness::Complete);
+    }
+
+    #[test]
+    fn test_keep() {
+        let limits = CycleLimits {
+            max_count: Some(5),
+            ..Default::default()
+        };
+        let found = find_bounded_simple_cycles(&mesh_graph(8), &limits, |cycle| {
+            cycle.iter().any(|node| node.index() == 7)
+        });
+        assert_eq!(found.completeness, Completeness::MaxCount);
+        assert_eq!(found.cycles.len(), 5);
+        assert!(found
+            .cycles
+            .iter()
+            .all(|cycle| cycle.iter().any(|node| node.index() == 7)));
+    }
+
+    #[test]
+    fn test_timeout() {
+        let limits = CycleLimits {
+            timeout: Some(Duration::ZERO),
+            ..Default::default()
+        };
+        let found = find_bounded_simple_cycles(&mesh_graph(9), &limits, |_| true);
+        assert_eq!(found.completeness, Completeness::Timeout);
+    }
+}
@@ -0,0 +1,380 @@
+use std::hash::Hash;
+
+use petgraph::{
+    algo::kosaraju_scc,
+    data::DataMap,
+    visit::{
+        EdgeRef, IntoEdgeReferences, IntoEdges, IntoEdgesDirected, IntoNodeIdentifiers,
+        NodeIndexable, Visitable,
+    },
+    Direction,
+};
+
+use crate::hash::HashSet;
+
+/// Index of no node in [`NodeLists`].
+const NONE: usize = usize::MAX;
+const SINKS: usize = 0;
+const SOURCES: usize = 1;
+/// Index of the first bucket of nodes by difference between outgoing and incoming edges.
+const FIRST_BUCKET: usize = 2;
+
+/// Doubly linked lists of nodes, identified by their positions in a component, where each node is
+/// in at most one list so that it can be moved between the lists in constant time.
+struct NodeLists {
+    heads: Vec<usize>,
+    tails: Vec<usize>,
+    previous: Vec<usize>,
+    next: Vec<usize>,
+    list_of: Vec<usize>,
+}
+
+impl NodeLists {
+    fn new(list_count: usize, node_count: usize) -> Self {
+        Self {
+            heads: vec![NONE; list_count],
+            tails: vec![NONE; list_count],
+            previous: vec![NONE; node_count],
+            next: vec![NONE; node_count],
+            list_of: vec![NONE; node_count],
+        }
+    }
+
+    fn first(&self, list: usize) -> Option<usize> {
+        let head = self.heads[list];
+        (head != NONE).then_some(head)
+    }
+
+    fn push_back(&mut self, list: usize, node: usize) {
+        let tail = self.tails[list];
+        self.previous[node] = tail;
+        self.next[node] = NONE;
+        if tail == NONE {
+            self.heads[list] = node;
+        } else {
+            self.next[tail] = node;
+        }
+        self.tails[list] = node;
+        self.list_of[node] = list;
+    }
+
+    fn remove(&mut self, node: usize) {
+        let list = self.list_of[node];
+        let (previous, next) = (self.previous[node], self.next[node]);
+        if previous == NONE {
+            self.heads[list] = next;
+        } else {
+            self.next[previous] = next;
+        }
+        if next == NONE {
+            self.tails[list] = previous;
+        } else {
+            self.previous[next] = previous;
+        }
+        self.list_of[node] = NONE;
+    }
+}
+
+/// Orders the nodes of a strongly connected component with the heuristic of Eades, Lin and
+/// Smyth: sinks go to the end, sources to the start, and otherwise the node with the largest
+/// difference between outgoing and incoming edges goes to the start. Edges going backwards in
+/// the order form a feedback arc set.
+///
+/// The nodes are kept in buckets by that difference, which takes linear time. `component` must
+/// be sorted by weight and `ranks` must give the position of each of its nodes in it: the nodes
+/// are then visited in that order, so that ties go to the smallest weight.
+fn eades_lin_smyth<G>(
+    graph: G,
+    component: &[G::NodeId],
+    component_of: &[usize],
+    ranks: &[usize],
+) -> Vec<usize>
+where
+    G: IntoEdgesDirected + NodeIndexable,
+{
+    let component_index = component_of[graph.to_index(component[0])];
+    let position_of = |node: G::NodeId| {
+        let index = graph.to_index(node);
+        (component_of[index] == component_index).then(|| ranks[index])
+    };
+    // Going through the nodes in order sorts the neighbors, whatever the order of the edges
+    let mut successors = vec![Vec::new(); component.len()];
+    let mut predecessors = vec![Vec::new(); component.len()];
+    for (position, &node) in component.iter().enumerate() {
+        for edge_ref in graph.edges_directed(node, Direction::Incoming) {
+            if let Some(source) = position_of(edge_ref.source()).filter(|s| *s != position) {
+                successors[source].push(position);
+            }
+        }
+        for edge_ref in graph.edges_directed(node, Direction::Outgoing) {
+            if let Some(target) = position_of(edge_ref.target()).filter(|t| *t != position) {
+                predecessors[target].push(position);
+            }
+        }
+    }
+    let mut out_degrees = successors.iter().map(Vec::len).collect::<Vec<_>>();
+    let mut in_degrees = predecessors.iter().map(Vec::len).collect::<Vec<_>>();
+    // The differences only go down to minus the largest in-degree
+    let offset = in_degrees.iter().copied().max().unwrap_or(0);
+    let bucket_count = offset + out_degrees.iter().copied().max().unwrap_or(0) + 1;
+    let list_for = |out_degree: usize, in_degree: usize| {
+        if out_degree == 0 {
+            SINKS
+        } else if in_degree == 0 {
+            SOURCES
+        } else {
+            FIRST_BUCKET + offset + out_degree - in_degree
+        }
+    };
+
+    let mut lists = NodeLists::new(FIRST_BUCKET + bucket_count, component.len());
+    let mut max_list = FIRST_BUCKET;
+    for position in 0..component.len() {
+        let list = list_for(out_degrees[position], in_degrees[position]);
+        lists.push_back(list, position);
+        max_list = max_list.max(list);
+    }
+
+    let mut removed = vec![false; component.len()];
+    let mut front = Vec::with_capacity(component.len());
+    let mut back = Vec::new();
+    loop {
+        let node = if let Some(node) = lists.first(SINKS) {
+            back.push(node);
+            node
+        } else if let Some(node) = lists.first(SOURCES) {
+            front.push(node);
+            node
+        } else {
+            while max_list >= FIRST_BUCKET && lists.first(max_list).is_none() {
+                max_list -= 1;
+            }
+            let Some(node) = (max_list >= FIRST_BUCKET)
+                .then(|| lists.first(max_list))
+                .flatten()
+            else {
+                break;
+            };
+            front.push(node);
+            node
+        };
+        lists.remove(node);
+        removed[node] = true;
+        for &successor in &successors[node] {
+            if !removed[successor] {
+                in_degrees[successor] -= 1;
+                lists.remove(successor);
+                let list = list_for(out_degrees[successor], in_degrees[successor]);
+                lists.push_back(list, successor);
+                max_list = max_list.max(list);
+            }
+        }
+        for &predecessor in &predecessors[node] {
+            if !removed[predecessor] {
+                out_degrees[predecessor] -= 1;
+                lists.remove(predecessor);
+                lists.push_back(
+                    list_for(out_degrees[predecessor], in_degrees[predecessor]),
+                    predecessor,
+                );
+            }
+        }
+    }
+    back.reverse();
+    front.extend(back);
+    front
+        .into_iter()
+        .map(|position| graph.to_index(component[position]))
+        .collect()
+}
+
+/// Whether `to` can be reached from `from` without going through the `skipped` edges. Only the
+/// nodes in the strongly connected component of `from` are visited, since a path leaving it
+/// can't come back. `visited` must be all false, and is left so.
+fn is_reachable<G>(
+    graph: G,
+    from: G::NodeId,
+    to: G::NodeId,
+    skipped: &HashSet<G::EdgeId>,
+    component_of: &[usize],
+    visited: &mut [bool],
+) -> bool
+where
+    G: IntoEdges + NodeIndexable,
+    G::EdgeId: Eq + Hash,
+{
+    let component = component_of[graph.to_index(from)];
+    visited[graph.to_index(from)] = true;
+    // Also the queue of the breadth-first search, from `next` on
+    let mut reached = vec![from];
+    let mut next = 0;
+    let mut is_reachable = false;
+    while let Some(&node) = reached.get(next) {
+        next += 1;
+        if node == to {
+            is_reachable = true;
+            break;
+        }
+        for edge_ref in graph.edges(node) {
+            let target = graph.to_index(edge_ref.target());
+            if !skipped.contains(&edge_ref.id())
+                && component_of[target] == component
+                && !visited[target]
+            {
+                visited[target] = true;
+                reached.push(edge_ref.target());
+            }
+        }
+    }
+    for node in reached {
+        visited[graph.to_index(node)] = false;
+    }
+    is_reachable
+}
+
+/// Returns a small set of edges whose removal makes `graph` acyclic, found with the heuristic of
+/// Eades, Lin and Smyth in each strongly connected component. The set is then made minimal:
+/// none of its edges can be put back without closing a cycle, which is checked with a search
+/// inside the component of the edge.
+///
+/// Ties are broken by the node weights rather than the node indices, so that the result only
+/// depends on the order of the weights when they are distinct.
+pub fn find_feedback_arc_set<G>(graph: G) -> Vec<G::EdgeId>
+where
+    G: IntoEdgesDirected
+        + IntoEdgeReferences
+        + IntoNodeIdentifiers
+        + NodeIndexable
+        + Visitable
+        + DataMap,
+    G::NodeWeight: Ord,
+    G::EdgeId: Eq + Hash,
+{
+    let mut components = kosaraju_scc(graph);
+    let mut component_of = vec![usize::MAX; graph.node_bound()];
+    let mut ranks = vec![0; graph.node_bound()];
+    for (component_index, component) in components.iter_mut().enumerate() {
+        component.sort_by(|a, b| graph.node_weight(*a).cmp(&graph.node_weight(*b)));
+        for (rank, node) in component.iter().enumerate() {
+            component_of[graph.to_index(*node)] = component_index;
+            ranks[graph.to_index(*node)] = rank;
+        }
+    }
+    let mut positions = vec![0; graph.node_bound()];
+    for component in components.iter().filter(|component| component.len() > 1) {
+        let order = eades_lin_smyth(graph, component, &component_of, &ranks);
+        for (position, index) in order.into_iter().enumerate() {
+            positions[index] = position;
+        }
+    }
+
+    let mut feedback_arcs = graph
+        .edge_references()
+        .filter(|edge_ref| {
+            let source = graph.to_index(edge_ref.source());
+            let target = graph.to_index(edge_ref.target());
+            component_of[source] == component_of[target] && positions[source] >= positions[target]
+        })
+        .map(|edge_ref| (edge_ref.id(), edge_ref.source(), edge_ref.target()))
+        .collect::<Vec<_>>();
+    // The edges that are put back depend on the order in which they are tried
+    feedback_arcs.sort_by(|(_, a_source, a_target), (_, b_source, b_target)| {
+        (graph.node_weight(*a_source), graph.node_weight(*a_target))
+            .cmp(&(graph.node_weight(*b_source), graph.node_weight(*b_target)))
+    });
+
+    let mut skipped = feedback_arcs
+        .iter()
+        .map(|(edge, _, _)| *edge)
+        .collect::<HashSet<_>>();
+    let mut visited = vec![false; graph.node_bound()];
+    feedback_arcs.retain(|(edge, source, target)| {
+        if source != target
+            && !is_reachable(
+                graph,
+                *target,
+                *source,
+                &skipped,
+                &component_of,
+                &mut visited,
+            )
+        {
+            skipped.remove(edge);
+            false
+        } else {
+            true
+        }
+    });
+    feedback_arcs.into_iter().map(|(edge, _, _)| edge).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::{algo::is_cyclic_directed, visit::EdgeFiltered, Graph};
+    use rustworkx_core::generators::complete_graph;
+
+    fn assert_acyclic_without(graph: &Graph<(), ()>, feedback_arcs: &[petgraph::graph::EdgeIndex]) {
+        let feedback_arcs = feedback_arcs.iter().copied().collect::<HashSet<_>>();
+        let filtered =
+            EdgeFiltered::from_fn(graph, |edge_ref| !feedback_arcs.contains(&edge_ref.id()));
+        assert!(!is_cyclic_directed(&filtered));
+    }
+
+    #[test]
+    fn test_acyclic() {
+        let graph = Graph::<(), ()>::from_edges([(0, 1), (1, 2), (0, 2)]);
+        assert!(find_feedback_arc_set(&graph).is_empty());
+    }
+
+    #[test]
+    fn test_single_back_edge() {
+        let graph = Graph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 0), (3, 3)]);
+        let feedback_arcs = find_feedback_arc_set(&graph);
+        assert_eq!(feedback_arcs.len(), 2);
+        assert!(feedback_arcs.contains(&graph.find_edge(3.into(), 3.into()).unwrap()));
+        assert_acyclic_without(&graph, &feedback_arcs);
+    }
+
+    #[test]
+    fn test_shared_edge() {
+        // Both cycles go through 2 -> 0
+        let graph = Graph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (0, 3), (3, 2)]);
+        let feedback_arcs = find_feedback_arc_set(&graph);
+        assert_eq!(
+            feedback_arcs,
+            [graph.find_edge(2.into(), 0.into()).unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_ties_by_weight() {
+        let mut graph = Graph::<&str, ()>::new();
+        let b = graph.add_node("b");
+        let a = graph.add_node("a");
+        graph.extend_with_edges([(a, b), (b, a)]);
+        assert_eq!(
+            find_feedback_arc_set(&graph),
+            [graph.find_edge(b, a).unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_large_ring() {
+        let node_count = 100_000;
+        let graph = Graph::<(), ()>::from_edges(
+            (0..node_count).map(|node| (node as u32, ((node + 1) % node_count) as u32)),
+        );
+        let feedback_arcs = find_feedback_arc_set(&graph);
+        assert_eq!(feedback_arcs.len(), 1);
+    }
+
+    #[test]
+    fn test_mesh_graph() {
+        let graph: Graph<(), ()> = complete_graph(Some(6), None, || (), || ()).unwrap();
+        let feedback_arcs = find_feedback_arc_set(&graph);
+        // Any order of the nodes leaves half of the edges going backwards
+        assert_eq!(feedback_arcs.len(), 15);
+        assert_acyclic_without(&graph, &feedback_arcs);
+    }
+}
@@ -168,7 +168,7 @@ where
         closed: HashSet::default(),
         block: HashMap::default(),
         stack: Vec::new(),
-        start_node: NodeIndex::new(std::u32::MAX as usize),
+        start_node: NodeIndex::new(u32::MAX as usize),
         node_map: HashMap::default(),
         reverse_node_map: HashMap::default(),
         subgraph: StableDiGraph::new(),
@@ -1,2 +1,4 @@
+pub mod bounded_simple_cycles;
+pub mod feedback_arc_set;
 pub mod johnson_simple_cycles;
 pub mod path_edges;
@@ -1,6 +1,9 @@
-use std::{hash::Hash, iter};
+use std::{collections::VecDeque, hash::Hash, iter};
 
-use petgraph::visit::{EdgeCount, EdgeRef, IntoEdgeReferences, IntoEdges, VisitMap, Visitable};
+use petgraph::{
+    data::DataMap,
+    visit::{EdgeCount, EdgeRef, IntoEdgeReferences, IntoEdges, VisitMap, Visitable},
+};
 
 use crate::hash::HashSet;
 
@@ -13,6 +16,7 @@ struct PathTreeNode<E> {
 pub struct TraversalSpace<G: Visitable> {
     graph: G,
     stack: Vec<(G::NodeId, Option<u32>)>,
+    queue: VecDeque<(G::NodeId, Option<u32>)>,
     discovered: G::Map,
     path_tree: Vec<PathTreeNode<G::EdgeId>>,
 }
@@ -25,12 +29,14 @@ impl<G: Visitable> TraversalSpace<G> {
         Self {
             graph,
             stack: vec![],
+            queue: VecDeque::new(),
             discovered: Default::default(),
             path_tree: vec![],
         }
     }
     fn reset(&mut self) {
         self.stack.clear();
+        self.queue.clear();
         self.graph.reset_map(&mut self.discovered);
         self.path_tree.clear();
     }
@@ -70,17 +76,7 @@ impl<G: Visitable> TraversalSpace<G> {
 
         while let Some((node, path_index)) = self.stack.pop() {
             if node == to {
-                let mut path_index = path_index;
-                let path_tree = self.path_tree.as_slice();
-                return Some(iter::from_fn(move || {
-                    if let Some(current_path_index) = path_index {
-                        let path_tree_node = path_tree[current_path_index as usize];
-                        path_index = path_tree_node.parent_index;
-                        Some(path_tree_node.edge)
-                    } else {
-                        None
-                    }
-                }));
+                return Some(self.backtrack(path_index));
             }
             if self.discovered.visit(node) {
                 for edge_ref in self.graph.edges(node) {
@@ -98,6 +94,63 @@ impl<G: Visitable> TraversalSpace<G> {
         }
         None
     }
+
+    /// Like [`find_backtrack_edges`](Self::find_backtrack_edges), but searches breadth-first so
+    /// that the path is one of the shortest from `from` to `to`. The neighbors are visited in the
+    /// order of their weights, so that among the shortest paths the one chosen doesn't depend on
+    /// the node indices or the order of the edges. Only the nodes for which `within` returns true
+    /// are visited.
+    pub fn find_shortest_backtrack_edges(
+        &mut self,
+        from: G::NodeId,
+        to: G::NodeId,
+        mut within: impl FnMut(G::NodeId) -> bool,
+    ) -> Option<impl Iterator<Item = G::EdgeId> + '_>
+    where
+        G: IntoEdges + DataMap,
+        G::NodeWeight: Ord,
+    {
+        self.reset();
+        self.discovered.visit(from);
+        self.queue.push_back((from, None));
+
+        let mut edge_refs = Vec::new();
+        while let Some((node, path_index)) = self.queue.pop_front() {
+            if node == to {
+                return Some(self.backtrack(path_index));
+            }
+            let graph = self.graph;
+            edge_refs.clear();
+            edge_refs.extend(graph.edges(node));
+            edge_refs.sort_by(|a, b| {
+                graph
+                    .node_weight(a.target())
+                    .cmp(&graph.node_weight(b.target()))
+            });
+            for edge_ref in &edge_refs {
+                let neighbor = edge_ref.target();
+                if within(neighbor) && self.discovered.visit(neighbor) {
+                    self.path_tree.push(PathTreeNode {
+                        edge: edge_ref.id(),
+                        parent_index: path_index,
+                    });
+                    self.queue
+                        .push_back((neighbor, Some((self.path_tree.len() - 1) as u32)));
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the edges from the path tree node at `path_index` up to the root.
+    fn backtrack(&self, mut path_index: Option<u32>) -> impl Iterator<Item = G::EdgeId> + '_ {
+        let path_tree = self.path_tree.as_slice();
+        iter::from_fn(move || {
+            let path_tree_node = path_tree[path_index? as usize];
+            path_index = path_tree_node.parent_index;
+            Some(path_tree_node.edge)
+        })
+    }
 }
 
 #[cfg(test)]
@@ -132,6 +185,36 @@ mod tests {
         assert!(edges.next().is_none());
     }
     #[test]
+    fn test_find_shortest_backtrack_edges() {
+        let graph = Graph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (1, 3), (2, 0)]);
+        let mut space = TraversalSpace::new(&graph);
+        let endpoints = space
+            .find_shortest_backtrack_edges(0.into(), 4.into(), |_| true)
+            .unwrap()
+            .map(|edge| graph.edge_endpoints(edge).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            endpoints,
+            [(3, 4), (1, 3), (0, 1)].map(|(from, to)| (from.into(), to.into()))
+        );
+        assert!(space
+            .find_shortest_backtrack_edges(4.into(), 0.into(), |_| true)
+            .is_none());
+    }
+    #[test]
+    fn test_find_shortest_backtrack_edges_ties() {
+        let mut graph = Graph::<&str, ()>::new();
+        let [a, c, b, d] = ["a", "c", "b", "d"].map(|weight| graph.add_node(weight));
+        graph.extend_with_edges([(a, c), (c, d), (a, b), (b, d)]);
+        let mut space = TraversalSpace::new(&graph);
+        let endpoints = space
+            .find_shortest_backtrack_edges(a, d, |_| true)
+            .unwrap()
+            .map(|edge| graph.edge_endpoints(edge).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(endpoints, [(b, d), (a, b)]);
+    }
+    #[test]
     fn test_find_edges_in_cycles_basic() {
         let graph = Graph::<(), ()>::from_edges([
             (0, 1),
@@ -0,0 +1,85 @@
+use std::{
+    io::{self, Write},
+    ops::Deref,
+};
+
+use camino::{FromPathError, Utf8Path};
+use petgraph::visit::{EdgeRef as _, IntoEdgeReferences as _, NodeIndexable as _};
+use serde::Serialize;
+
+use super::count;
+use crate::dep_graph::DependencyGraph;
+
+/// A strongly connected component of the dependency graph: every file in it imports every other
+/// one, directly or indirectly.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Component<'a> {
+    /// Number of files in the component.
+    pub size: usize,
+    /// Files in the component, sorted.
+    pub files: Vec<&'a Utf8Path>,
+    /// Number of imports between the files of the component.
+    pub imports: usize,
+}
+
+/// Returns the components that contain a cycle, with the largest ones first.
+pub fn cyclic_components<E>(
+    dependency_graph: &DependencyGraph<E>,
+) -> Result<Vec<Component<'_>>, FromPathError> {
+    let path_graph = dependency_graph.path_graph();
+    let components = dependency_graph.find_cyclic_components();
+    let mut component_of = vec![usize::MAX; path_graph.node_bound()];
+    for (index, nodes) in components.iter().enumerate() {
+        for node in nodes {
+            component_of[node.index()] = index;
+        }
+    }
+    let mut import_counts = vec![0; components.len()];
+    for edge_ref in path_graph.edge_references() {
+        let component = component_of[edge_ref.source().index()];
+        if component != usize::MAX && component == component_of[edge_ref.target().index()] {
+            import_counts[component] += 1;
+        }
+    }
+    components
+        .into_iter()
+        .zip(import_counts)
+        .map(|(nodes, imports)| {
+            let files = nodes
+                .into_iter()
+                .map(|node| path_graph[node].deref().try_into())
+                .collect::<Result<Vec<&Utf8Path>, FromPathError>>()?;
+            Ok(Component {
+                size: files.len(),
+                files,
+                imports,
+            })
+        })
+        .collect()
+}
+
+pub fn write_text(components: &[Component], stdout: &mut dyn Write) -> io::Result<()> {
+    for (index, component) in components.iter().enumerate() {
+        writeln!(
+            stdout,
+            "Component {}: {}, {}",
+            index + 1,
+            count(component.size, "file"),
+            count(component.imports, "import")
+        )?;
+        for file in &component.files {
+            writeln!(stdout, "    {file}")?;
+        }
+    }
+    writeln!(
+        stdout,
+        "Found {} with cycles",
+        count(components.len(), "component")
+    )
+}
+
+pub fn write_json(components: &[Component], stdout: &mut dyn Write) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *stdout, components)?;
+    writeln!(stdout)
+}
@@ -0,0 +1,252 @@
+//! The configuration file, `decycle.json` or the `decycle` key of package.json in the current
+//! directory, for instance:
+//!
+//! ```json
+//! {
+//!   "entries": ["src/main.ts"],
+//!   "include": ["src/pages/**/*.tsx"],
+//!   "resolver": {
+//!     "bareSpecifiers": true,
+//!     "conditions": ["import"],
+//!     "alias": { "@": "./src" }
+//!   },
+//!   "ignore": [{ "source": "src/legacy/**", "target": "**" }],
+//!   "ignoreDynamicImports": true,
+//!   "traceBarrels": true,
+//!   "importKinds": "both",
+//!   "runtimeOnly": true,
+//!   "snapshot": "decycle-snapshot.json",
+//!   "format": "json",
+//!   "limits": { "maxCycleLength": 10, "timeout": 30 },
+//!   "layers": [{ "name": "ui", "include": ["src/ui/**"], "mayImport": ["domain"] }]
+//! }
+//! ```
+//!
+//! Paths and patterns are relative to the current directory. Command line flags take precedence
+//! over the configuration. The `layers` are documented in `src/cli/layers.rs`.
+
+use std::{
+    fmt::{self, Display},
+    io,
+    path::{Path, PathBuf},
+};
+
+use glob::Pattern;
+use serde::Deserialize;
+
+use super::{
+    entries::{matches_pattern, parse_pattern, EntriesError},
+    layers::Layer,
+    resolver::ResolverOptions,
+    CycleLimitArgs, ImportKinds, OutputFormat,
+};
+
+pub const CONFIG_FILE_NAME: &str = "decycle.json";
+
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct Config {
+    pub entries: Vec<String>,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub resolver: ResolverOptions,
+    /// Imports left out of the dependency graph.
+    pub ignore: Vec<IgnoredImport>,
+    /// Whether imports of a file that are all dynamic `import()` are left out.
+    pub ignore_dynamic_imports: bool,
+    /// Whether imports are traced through the re-exports of barrel files.
+    pub trace_barrels: bool,
+    pub import_kinds: Option<ImportKinds>,
+    /// Whether only the cycles that can fail at runtime are checked and listed.
+    pub runtime_only: bool,
+    pub snapshot: Option<PathBuf>,
+    pub format: Option<OutputFormat>,
+    pub limits: CycleLimitArgs,
+    /// Rules of the `layers` command.
+    pub layers: Vec<Layer>,
+}
+
+/// Imports from the files matching `source` to the files matching `target`.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct IgnoredImport {
+    pub source: String,
+    pub target: String,
+}
+
+#[derive(Deserialize, Default)]
+struct PackageJsonConfig {
+    decycle: Option<Config>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, io::Error),
+    Parse(PathBuf, serde_json::Error),
+    Pattern(EntriesError),
+    /// A resolver option that has no effect unless bare specifiers are resolved.
+    RequiresBareSpecifiers(&'static str),
+    InvalidMaxCycleLength,
+    /// The configured format isn't supported by the command.
+    UnsupportedFormat(&'static str),
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, err) => write!(f, "failed to read {}: {err}", path.display()),
+            ConfigError::Parse(path, err) => write!(f, "invalid {}: {err}", path.display()),
+            ConfigError::Pattern(err) => err.fmt(f),
+            ConfigError::RequiresBareSpecifiers(option) => {
+                write!(
+                    f,
+                    "the `{option}` resolver option only applies to bare specifiers, enable them \
+                     with `--bare-specifiers` or `\"bareSpecifiers\": true`"
+                )
+            }
+            ConfigError::InvalidMaxCycleLength => {
+                write!(f, "`maxCycleLength` must be at least 1")
+            }
+            ConfigError::UnsupportedFormat(command) => write!(
+                f,
+                "the `{command}` command doesn't support the format set in the configuration"
+            ),
+        }
+    }
+}
+
+impl From<EntriesError> for ConfigError {
+    fn from(err: EntriesError) -> Self {
+        ConfigError::Pattern(err)
+    }
+}
+
+/// Reads the configuration at `path` if given, or else from `decycle.json` or package.json in
+/// `cwd`. Without any, the configuration is empty.
+pub fn load_config(cwd: &Path, path: Option<&Path>) -> Result<Config, ConfigError> {
+    let config = if let Some(path) = path {
+        let path = cwd.join(path);
+        let content =
+            std::fs::read_to_string(&path).map_err(|err| ConfigError::Read(path.clone(), err))?;
+        parse(&path, &content)?
+    } else if let Some(content) = read_optional(&cwd.join(CONFIG_FILE_NAME))? {
+        parse(&cwd.join(CONFIG_FILE_NAME), &content)?
+    } else if let Some(content) = read_optional(&cwd.join("package.json"))? {
+        parse::<PackageJsonConfig>(&cwd.join("package.json"), &content)?
+            .decycle
+            .unwrap_or_default()
+    } else {
+        Config::default()
+    };
+    config.resolver.check_bare_specifiers()?;
+    if config.limits.max_cycle_length == Some(0) {
+        return Err(ConfigError::InvalidMaxCycleLength);
+    }
+    Ok(config)
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>, ConfigError> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(ConfigError::Read(path.to_path_buf(), err)),
+    }
+}
+
+fn parse<'a, T: Deserialize<'a>>(path: &Path, content: &'a str) -> Result<T, ConfigError> {
+    serde_json::from_str(content).map_err(|err| ConfigError::Parse(path.to_path_buf(), err))
+}
+
+/// The compiled `ignore` patterns of a configuration.
+pub struct IgnoredImports {
+    patterns: Vec<(Pattern, Pattern)>,
+}
+
+impl IgnoredImports {
+    pub fn new(ignore: &[IgnoredImport]) -> Result<Self, ConfigError> {
+        let patterns = ignore
+            .iter()
+            .map(|import| {
+                Ok((
+                    parse_pattern(&import.source)?,
+                    parse_pattern(&import.target)?,
+                ))
+            })
+            .collect::<Result<_, EntriesError>>()?;
+        Ok(Self { patterns })
+    }
+
+    pub fn contains(&self, source: &Path, target: &Path) -> bool {
+        self.patterns
+            .iter()
+            .any(|(source_pattern, target_pattern)| {
+                matches_pattern(source_pattern, source) && matches_pattern(target_pattern, target)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::test_dir::TestDir;
+
+    #[test]
+    fn test_load_config() {
+        let dir = TestDir::new(
+            "config-load",
+            &[
+                (
+                    "decycle.json",
+                    r#"{ "entries": ["a.ts"], "format": "json" }"#,
+                ),
+                ("package.json", r#"{ "decycle": { "entries": ["b.ts"] } }"#),
+                ("other.json", r#"{ "limits": { "maxCycles": 10 } }"#),
+            ],
+        );
+        let config = load_config(&dir, None).unwrap();
+        assert_eq!(config.entries, ["a.ts"]);
+        assert!(matches!(config.format, Some(OutputFormat::Json)));
+
+        let config = load_config(&dir, Some(Path::new("other.json"))).unwrap();
+        assert!(config.entries.is_empty());
+        assert_eq!(config.limits.max_cycles, Some(10));
+
+        std::fs::remove_file(dir.join("decycle.json")).unwrap();
+        assert_eq!(load_config(&dir, None).unwrap().entries, ["b.ts"]);
+
+        std::fs::write(dir.join("package.json"), r#"{ "name": "x" }"#).unwrap();
+        assert!(load_config(&dir, None).unwrap().entries.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let dir = TestDir::new(
+            "config-invalid",
+            &[("decycle.json", r#"{ "entry": ["a.ts"] }"#)],
+        );
+        let err = load_config(&dir, None).unwrap_err();
+        assert!(err.to_string().contains("unknown field `entry`"));
+
+        std::fs::write(
+            dir.join("decycle.json"),
+            r#"{ "resolver": { "alias": { "@": "./src" } } }"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            load_config(&dir, None),
+            Err(ConfigError::RequiresBareSpecifiers("alias"))
+        ));
+    }
+
+    #[test]
+    fn test_ignored_imports() {
+        let ignored = IgnoredImports::new(&[IgnoredImport {
+            source: "legacy/**".to_owned(),
+            target: "*.ts".to_owned(),
+        }])
+        .unwrap();
+        assert!(ignored.contains(Path::new("legacy/a/b.ts"), Path::new("c.ts")));
+        assert!(!ignored.contains(Path::new("legacy/a/b.ts"), Path::new("d/c.ts")));
+        assert!(!ignored.contains(Path::new("c.ts"), Path::new("legacy/a.ts")));
+    }
+}
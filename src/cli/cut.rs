@@ -0,0 +1,121 @@
+use std::{
+    io::{self, Write},
+    ops::Deref,
+    path::Path,
+};
+
+use camino::{FromPathError, Utf8Path};
+use serde::Serialize;
+
+use super::{
+    count,
+    report::{import_locations, write_source_locations, CycleSearch, SourceLocation},
+    source::SourceCache,
+    JsGraph,
+};
+use crate::{
+    algorithms::{bounded_simple_cycles::CycleLimits, feedback_arc_set::find_feedback_arc_set},
+    hash::HashMap,
+};
+
+/// Imports whose removal breaks all the cycles, with the ones in the most cycles first.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Cut<'a> {
+    pub edges: Vec<CutEdge<'a>>,
+    /// Number of simple cycles the counts are based on.
+    pub cycles: usize,
+    pub cycle_search: CycleSearch,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CutEdge<'a> {
+    pub source: &'a Utf8Path,
+    pub target: &'a Utf8Path,
+    pub imports: Vec<SourceLocation>,
+    /// Number of simple cycles going through this import.
+    pub cycles: usize,
+}
+
+impl<'a> Cut<'a> {
+    /// The imports are ranked by the number of cycles found within `limits` that go through them.
+    pub fn new(
+        graph: &'a JsGraph,
+        limits: &CycleLimits,
+        sources: &mut SourceCache,
+    ) -> Result<Self, FromPathError> {
+        let dependency_graph = &graph.dependency_graph;
+        let path_graph = dependency_graph.path_graph();
+
+        let (cycles, completeness) = dependency_graph.find_cycles(limits, |_| true);
+        let mut cycle_count = 0;
+        let mut cycles_by_edge = HashMap::<(&Path, &Path), usize>::default();
+        for cycle in cycles {
+            cycle_count += 1;
+            for (from, to, _) in cycle {
+                *cycles_by_edge.entry((from, to)).or_default() += 1;
+            }
+        }
+
+        let mut edges = find_feedback_arc_set(path_graph)
+            .into_iter()
+            .map(|edge| {
+                let (from, to) = path_graph.edge_endpoints(edge).unwrap();
+                let (source, target) = (path_graph[from].deref(), path_graph[to].deref());
+                let cycles = cycles_by_edge.get(&(source, target)).copied().unwrap_or(0);
+                let source: &Utf8Path = source.try_into()?;
+                Ok(CutEdge {
+                    source,
+                    target: target.try_into()?,
+                    imports: import_locations(sources, source, &path_graph[edge]),
+                    cycles,
+                })
+            })
+            .collect::<Result<Vec<_>, FromPathError>>()?;
+        edges.sort_unstable_by(|a, b| {
+            b.cycles
+                .cmp(&a.cycles)
+                .then_with(|| (a.source, a.target).cmp(&(b.source, b.target)))
+        });
+        Ok(Self {
+            edges,
+            cycles: cycle_count,
+            cycle_search: completeness.into(),
+        })
+    }
+
+    pub fn write_text(&self, stdout: &mut dyn Write) -> io::Result<()> {
+        if self.edges.is_empty() {
+            return writeln!(stdout, "Found no cycles");
+        }
+        writeln!(
+            stdout,
+            "Removing {} breaks all the cycles:",
+            count(self.edges.len(), "import")
+        )?;
+        for edge in &self.edges {
+            writeln!(
+                stdout,
+                "  {} -> {}, in {} of {}",
+                edge.source,
+                edge.target,
+                edge.cycles,
+                count(self.cycles, "cycle")
+            )?;
+            write_source_locations(stdout, edge.source, &edge.imports)?;
+        }
+        if self.cycle_search != CycleSearch::Complete {
+            writeln!(
+                stdout,
+                "The search for cycles stopped early, so the counts are lower bounds"
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn write_json(&self, stdout: &mut dyn Write) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *stdout, self)?;
+        writeln!(stdout)
+    }
+}
@@ -0,0 +1,184 @@
+use std::{
+    fmt::{self, Display},
+    path::Path,
+    sync::Arc,
+};
+
+use oxc_diagnostics::{GraphicalReportHandler, LabeledSpan, NamedSource, OxcDiagnostic};
+
+use super::{count, source::SourceCache};
+use crate::{hash::HashMap, js_resolver::JsDiscoverDependencyError};
+
+/// Number of errors of each kind, printed after the diagnostics.
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct ErrorSummary {
+    pub read_errors: usize,
+    pub parse_errors: usize,
+    pub resolve_errors: usize,
+    pub non_literal_imports: usize,
+}
+
+impl Display for ErrorSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts = [
+            (self.read_errors, "unreadable file"),
+            (self.parse_errors, "parse error"),
+            (self.resolve_errors, "unresolved import"),
+            (self.non_literal_imports, "non-literal import"),
+        ];
+        let mut first = true;
+        for (n, noun) in counts {
+            if n == 0 {
+                continue;
+            }
+            if !first {
+                f.write_str(", ")?;
+            }
+            first = false;
+            f.write_str(&count(n, noun))?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders the errors found while scanning `path` into `out`, with the source snippets that
+/// they point to.
+fn render_error(
+    handler: &GraphicalReportHandler,
+    path: &Path,
+    error: &JsDiscoverDependencyError,
+    sources: &mut SourceCache,
+    summary: &mut ErrorSummary,
+    out: &mut impl fmt::Write,
+) -> fmt::Result {
+    let name = path.to_string_lossy();
+    let (parse_errors, resolve_errors, non_literal_imports) = match error {
+        JsDiscoverDependencyError::FileReadError(err) => {
+            summary.read_errors += 1;
+            let diagnostic = OxcDiagnostic::error(format!("failed to read {name}: {err}"));
+            return handler.render_report(out, &diagnostic);
+        }
+        JsDiscoverDependencyError::ParseOrResolveError {
+            parse_errors,
+            resolve_errors,
+            non_literal_imports,
+        } => (parse_errors, resolve_errors, non_literal_imports),
+    };
+    summary.parse_errors += parse_errors.len();
+    summary.resolve_errors += resolve_errors.len();
+    summary.non_literal_imports += non_literal_imports.len();
+
+    let diagnostics = parse_errors
+        .iter()
+        .cloned()
+        .chain(resolve_errors.iter().map(|(err, span)| {
+            OxcDiagnostic::error(format!("failed to resolve import: {err}")).with_label(
+                LabeledSpan::new_with_span(Some("imported here".into()), *span),
+            )
+        }))
+        .chain(non_literal_imports.iter().map(|span| {
+            OxcDiagnostic::warn("import with a non-literal specifier can't be followed")
+                .with_label(*span)
+        }));
+
+    // The file has been read by the resolver, so it's unlikely to fail here. If it does, the
+    // diagnostics are still worth rendering without snippets.
+    let source = sources
+        .get(path)
+        .ok()
+        .map(|source| Arc::clone(source.content()));
+    for diagnostic in diagnostics {
+        match &source {
+            Some(source) => {
+                let report =
+                    diagnostic.with_source_code(NamedSource::new(name.clone(), source.clone()));
+                handler.render_report(out, report.as_ref())?;
+            }
+            None => handler.render_report(out, &diagnostic)?,
+        }
+    }
+    Ok(())
+}
+
+/// Renders all the errors in `errors_by_path` into `out`, sorted by path, and returns how many
+/// errors of each kind there are.
+pub fn render_errors(
+    errors_by_path: &HashMap<Arc<Path>, JsDiscoverDependencyError>,
+    sources: &mut SourceCache,
+    out: &mut impl fmt::Write,
+) -> Result<ErrorSummary, fmt::Error> {
+    let handler = GraphicalReportHandler::new();
+    let mut errors_by_path = errors_by_path.iter().collect::<Vec<_>>();
+    errors_by_path.sort_unstable_by_key(|(path, _)| *path);
+
+    let mut summary = ErrorSummary::default();
+    for (path, error) in errors_by_path {
+        render_error(&handler, path, error, sources, &mut summary, out)?;
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use oxc_span::Span;
+
+    use super::*;
+    use crate::cli::test_dir::TestDir;
+
+    #[test]
+    fn test_error_summary() {
+        assert_eq!(ErrorSummary::default().to_string(), "");
+        assert_eq!(
+            ErrorSummary {
+                read_errors: 0,
+                parse_errors: 1,
+                resolve_errors: 2,
+                non_literal_imports: 1,
+            }
+            .to_string(),
+            "1 parse error, 2 unresolved imports, 1 non-literal import"
+        );
+    }
+
+    #[test]
+    fn test_render_errors() {
+        let dir = TestDir::new(
+            "render-errors",
+            &[("a.js", "import './missing';\nrequire(name);\n")],
+        );
+
+        let resolve_error = oxc_resolver::ResolveError::NotFound("./missing".into());
+        let mut errors_by_path = HashMap::default();
+        errors_by_path.insert(
+            Arc::from(Path::new("a.js")),
+            JsDiscoverDependencyError::ParseOrResolveError {
+                parse_errors: vec![],
+                resolve_errors: vec![(resolve_error, Span::new(7, 18))],
+                non_literal_imports: vec![Span::new(28, 32)],
+            },
+        );
+        errors_by_path.insert(
+            Arc::from(Path::new("b.js")),
+            JsDiscoverDependencyError::FileReadError(std::io::ErrorKind::NotFound.into()),
+        );
+
+        let mut out = String::new();
+        let summary =
+            render_errors(&errors_by_path, &mut SourceCache::new(&dir), &mut out).unwrap();
+        assert_eq!(
+            summary,
+            ErrorSummary {
+                read_errors: 1,
+                parse_errors: 0,
+                resolve_errors: 1,
+                non_literal_imports: 1,
+            }
+        );
+        assert!(out.contains("failed to resolve import"));
+        assert!(out.contains("[a.js:1:8]"));
+        assert!(out.contains("import './missing';"));
+        assert!(out.contains("imported here"));
+        assert!(out.contains("[a.js:2:9]"));
+        assert!(out.contains("failed to read b.js"));
+    }
+}
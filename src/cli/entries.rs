@@ -0,0 +1,125 @@
+use std::{
+    fmt::{self, Display},
+    path::{Component, Path, PathBuf},
+};
+
+use glob::{MatchOptions, Pattern, PatternError};
+use indexmap::IndexSet;
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+#[derive(Debug)]
+pub enum EntriesError {
+    InvalidPattern(String, PatternError),
+    Glob(glob::GlobError),
+    NoEntries,
+}
+
+impl Display for EntriesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntriesError::InvalidPattern(pattern, err) => {
+                write!(f, "invalid glob pattern `{pattern}`: {err}")
+            }
+            EntriesError::Glob(err) => err.fmt(f),
+            EntriesError::NoEntries => f.write_str("no entry files found"),
+        }
+    }
+}
+
+pub fn parse_pattern(pattern: &str) -> Result<Pattern, EntriesError> {
+    Pattern::new(pattern).map_err(|err| EntriesError::InvalidPattern(pattern.to_owned(), err))
+}
+
+/// Strips `.` components and collapses `..` ones into their parent, so that `./a.js`,
+/// `src/../a.js` and `a.js` name the same entry. Symbolic links aren't followed.
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut components = Vec::<Component>::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match components.last() {
+                Some(Component::Normal(_)) => {
+                    components.pop();
+                }
+                // `..` of the root is the root
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                _ => components.push(component),
+            },
+            _ => components.push(component),
+        }
+    }
+    components.into_iter().collect()
+}
+
+/// Returns `path`, relative to `base_path` or absolute, as a normalized path relative to
+/// `base_path`, the way the files are named in the graph.
+pub fn relative_to(base_path: &Path, path: &Path) -> PathBuf {
+    let path = normalize(&base_path.join(path));
+    match pathdiff::diff_paths(&path, normalize(base_path)) {
+        Some(relative_path) => relative_path,
+        None => path,
+    }
+}
+
+/// Returns the files and directories matching `pattern`. The pattern and the returned paths are
+/// relative to `base_path`.
+pub fn glob_relative(base_path: &Path, pattern: &str) -> Result<Vec<PathBuf>, EntriesError> {
+    // Validate the pattern by itself so that errors don't mention the base path
+    parse_pattern(pattern)?;
+    let escaped_base_path = Pattern::escape(&base_path.to_string_lossy());
+    let absolute_pattern = Path::new(&escaped_base_path).join(pattern);
+    let paths = glob::glob_with(&absolute_pattern.to_string_lossy(), MATCH_OPTIONS)
+        .map_err(|err| EntriesError::InvalidPattern(pattern.to_owned(), err))?;
+    let mut matches = vec![];
+    for path in paths {
+        let path = path.map_err(EntriesError::Glob)?;
+        if let Ok(relative_path) = path.strip_prefix(base_path) {
+            matches.push(normalize(relative_path));
+        }
+    }
+    Ok(matches)
+}
+
+/// Whether `path` matches `pattern`, with the same rules as [`glob_relative`].
+pub fn matches_pattern(pattern: &Pattern, path: &Path) -> bool {
+    pattern.matches_path_with(path, MATCH_OPTIONS)
+}
+
+/// Returns `entries` followed by the files matching `include` patterns, with the ones matching
+/// `exclude` patterns removed. The patterns are relative to `base_path`, and so are the returned
+/// paths, while `entries` may also be absolute.
+pub fn discover_entries(
+    base_path: &Path,
+    entries: &[String],
+    include: &[String],
+    exclude: &[String],
+) -> Result<Vec<PathBuf>, EntriesError> {
+    let exclude = exclude
+        .iter()
+        .map(|pattern| parse_pattern(pattern))
+        .collect::<Result<Vec<Pattern>, EntriesError>>()?;
+
+    let mut discovered = entries
+        .iter()
+        .map(|entry| relative_to(base_path, Path::new(entry)))
+        .collect::<IndexSet<PathBuf>>();
+
+    for pattern in include {
+        for path in glob_relative(base_path, pattern)? {
+            if base_path.join(&path).is_file() {
+                discovered.insert(path);
+            }
+        }
+    }
+
+    discovered.retain(|path| !exclude.iter().any(|pattern| matches_pattern(pattern, path)));
+    if discovered.is_empty() {
+        return Err(EntriesError::NoEntries);
+    }
+    Ok(discovered.into_iter().collect())
+}
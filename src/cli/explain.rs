@@ -0,0 +1,54 @@
+use std::{io::Write, ops::Deref};
+
+use camino::{FromPathError, Utf8Path};
+use petgraph::stable_graph::NodeIndex;
+
+use super::{
+    count,
+    report::{import_locations, write_source_locations},
+    source::SourceCache,
+    Error, JsGraph,
+};
+use crate::algorithms::path_edges::TraversalSpace;
+
+/// Writes one of the shortest import paths from `from` to `to`, with the import lines of each
+/// hop. Ties go to the path whose files come first. Returns whether there is such a path.
+pub fn write_import_path(
+    graph: &JsGraph,
+    from: NodeIndex,
+    to: NodeIndex,
+    sources: &mut SourceCache,
+    stdout: &mut dyn Write,
+) -> Result<bool, Error> {
+    let path_graph = graph.dependency_graph.path_graph();
+    let path = |node: NodeIndex| -> Result<&Utf8Path, FromPathError> {
+        path_graph[node].deref().try_into()
+    };
+    let mut space = TraversalSpace::new(path_graph);
+    let Some(backtrack_edges) = space.find_shortest_backtrack_edges(from, to, |_| true) else {
+        writeln!(
+            stdout,
+            "{} doesn't import {}, directly or indirectly",
+            path(from)?,
+            path(to)?
+        )?;
+        return Ok(false);
+    };
+    let mut edges = backtrack_edges.collect::<Vec<_>>();
+    edges.reverse();
+    writeln!(
+        stdout,
+        "{} reaches {} in {}:",
+        path(from)?,
+        path(to)?,
+        count(edges.len(), "import")
+    )?;
+    for edge in edges {
+        let (edge_from, edge_to) = path_graph.edge_endpoints(edge).unwrap();
+        let (edge_from, edge_to) = (path(edge_from)?, path(edge_to)?);
+        writeln!(stdout, "  {edge_from} -> {edge_to}")?;
+        let locations = import_locations(sources, edge_from, &path_graph[edge]);
+        write_source_locations(stdout, edge_from, &locations)?;
+    }
+    Ok(true)
+}
@@ -0,0 +1,164 @@
+use std::{
+    collections::VecDeque,
+    io::{self, Write},
+    ops::Deref,
+    path::Path,
+    sync::Arc,
+};
+
+use petgraph::{
+    stable_graph::{EdgeIndex, NodeIndex, StableDiGraph},
+    visit::{EdgeRef as _, IntoEdgeReferences as _},
+    Direction,
+};
+
+use super::count;
+use crate::{
+    algorithms::path_edges::TraversalSpace,
+    dep_graph::DependencyGraph,
+    hash::{HashMap, HashSet},
+};
+
+/// Which part of the dependency graph to print.
+pub enum Scope {
+    All,
+    /// Files in strongly connected components, i.e. the ones that are part of a cycle.
+    Cycles,
+    /// Files within `depth` imports of `node`, in either direction.
+    Around {
+        node: NodeIndex,
+        depth: usize,
+    },
+}
+
+/// A subgraph of the dependency graph, with nodes and edges sorted by path.
+pub struct Subgraph<'a, E> {
+    path_graph: &'a StableDiGraph<Arc<Path>, E>,
+    nodes: Vec<NodeIndex>,
+    edges: Vec<EdgeIndex>,
+    cycle_edges: HashSet<EdgeIndex>,
+}
+
+impl<'a, E> Subgraph<'a, E> {
+    pub fn new(dependency_graph: &'a DependencyGraph<E>, scope: Scope) -> Self {
+        let path_graph = dependency_graph.path_graph();
+        let node_set = match scope {
+            Scope::All => path_graph.node_indices().collect::<HashSet<_>>(),
+            Scope::Cycles => dependency_graph
+                .find_cyclic_components()
+                .into_iter()
+                .flatten()
+                .collect(),
+            Scope::Around { node, depth } => {
+                let mut node_set = HashSet::default();
+                node_set.insert(node);
+                let mut queue = VecDeque::from([(node, 0)]);
+                while let Some((node, distance)) = queue.pop_front() {
+                    if distance == depth {
+                        continue;
+                    }
+                    let neighbors = path_graph
+                        .neighbors_directed(node, Direction::Outgoing)
+                        .chain(path_graph.neighbors_directed(node, Direction::Incoming));
+                    for neighbor in neighbors {
+                        if node_set.insert(neighbor) {
+                            queue.push_back((neighbor, distance + 1));
+                        }
+                    }
+                }
+                node_set
+            }
+        };
+
+        let mut nodes = node_set.iter().copied().collect::<Vec<_>>();
+        nodes.sort_unstable_by_key(|node| &path_graph[*node]);
+        let mut edges = path_graph
+            .edge_references()
+            .filter(|edge_ref| {
+                node_set.contains(&edge_ref.source()) && node_set.contains(&edge_ref.target())
+            })
+            .map(|edge_ref| edge_ref.id())
+            .collect::<Vec<_>>();
+        edges.sort_unstable_by_key(|edge| {
+            let (from, to) = path_graph.edge_endpoints(*edge).unwrap();
+            (&path_graph[from], &path_graph[to])
+        });
+        let cycle_edges = TraversalSpace::new(path_graph).find_edges_in_cycles();
+        Self {
+            path_graph,
+            nodes,
+            edges,
+            cycle_edges,
+        }
+    }
+
+    fn endpoints(&self, edge: EdgeIndex) -> (&'a Path, &'a Path) {
+        let (from, to) = self.path_graph.edge_endpoints(edge).unwrap();
+        (self.path_graph[from].deref(), self.path_graph[to].deref())
+    }
+
+    pub fn write_text(&self, stdout: &mut dyn Write) -> io::Result<()> {
+        for edge in &self.edges {
+            let (from, to) = self.endpoints(*edge);
+            writeln!(stdout, "{} -> {}", from.display(), to.display())?;
+        }
+        writeln!(
+            stdout,
+            "{}, {}",
+            count(self.nodes.len(), "file"),
+            count(self.edges.len(), "import")
+        )
+    }
+
+    /// Writes the subgraph in the Graphviz DOT language, with edges in cycles colored red.
+    pub fn write_dot(&self, stdout: &mut dyn Write) -> io::Result<()> {
+        writeln!(stdout, "digraph {{")?;
+        for node in &self.nodes {
+            writeln!(stdout, "    {};", dot_id(&self.path_graph[*node]))?;
+        }
+        for edge in &self.edges {
+            let (from, to) = self.endpoints(*edge);
+            write!(stdout, "    {} -> {}", dot_id(from), dot_id(to))?;
+            if self.cycle_edges.contains(edge) {
+                write!(stdout, " [color=red]")?;
+            }
+            writeln!(stdout, ";")?;
+        }
+        writeln!(stdout, "}}")
+    }
+
+    /// Writes the subgraph as a Mermaid flowchart, with edges in cycles colored red.
+    pub fn write_mermaid(&self, stdout: &mut dyn Write) -> io::Result<()> {
+        writeln!(stdout, "flowchart LR")?;
+        // Paths aren't valid Mermaid ids, so nodes are named after their position instead
+        let mut ids = HashMap::<NodeIndex, usize>::default();
+        for (id, node) in self.nodes.iter().enumerate() {
+            ids.insert(*node, id);
+            let label = self.path_graph[*node]
+                .to_string_lossy()
+                .replace('"', "#quot;");
+            writeln!(stdout, "    n{id}[\"{label}\"]")?;
+        }
+        let mut cycle_links = vec![];
+        for (link_index, edge) in self.edges.iter().enumerate() {
+            let (from, to) = self.path_graph.edge_endpoints(*edge).unwrap();
+            writeln!(stdout, "    n{} --> n{}", ids[&from], ids[&to])?;
+            if self.cycle_edges.contains(edge) {
+                cycle_links.push(link_index.to_string());
+            }
+        }
+        if !cycle_links.is_empty() {
+            writeln!(stdout, "    linkStyle {} stroke:red", cycle_links.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+fn dot_id(path: &Path) -> String {
+    format!(
+        "\"{}\"",
+        path.to_string_lossy()
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+    )
+}
@@ -0,0 +1,150 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use glob::Pattern;
+
+use super::{
+    entries::{matches_pattern, parse_pattern, EntriesError},
+    workspace::{read_package_json, WorkspaceError},
+};
+use crate::hash::HashMap;
+
+/// How files are grouped, for instance into packages.
+pub enum Grouping {
+    /// By the nearest package.json, named after the package, or after its directory with a
+    /// trailing slash when it has no name, which no package name has. Files outside of any
+    /// package are left out.
+    Packages,
+    /// By the first pattern matching the file path. Files matching no pattern are left out.
+    Patterns(Vec<(String, Pattern)>),
+}
+
+/// Parses a `NAME=PATTERN` command line argument.
+pub fn parse_group(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((name, pattern)) if !name.is_empty() => Ok((name.to_owned(), pattern.to_owned())),
+        _ => Err("expected NAME=PATTERN".to_owned()),
+    }
+}
+
+impl Grouping {
+    /// Groups by `patterns`, or by package if there are none.
+    pub fn new(patterns: &[(String, String)]) -> Result<Self, EntriesError> {
+        if patterns.is_empty() {
+            return Ok(Grouping::Packages);
+        }
+        patterns
+            .iter()
+            .map(|(name, pattern)| Ok((name.clone(), parse_pattern(pattern)?)))
+            .collect::<Result<_, _>>()
+            .map(Grouping::Patterns)
+    }
+
+    /// Returns the group of each of the `paths` that belongs to one. Paths are relative to
+    /// `base_path`.
+    pub fn group_paths<'a>(
+        &self,
+        base_path: &Path,
+        paths: impl Iterator<Item = &'a Arc<Path>>,
+    ) -> Result<HashMap<Arc<Path>, Arc<str>>, WorkspaceError> {
+        let mut groups = HashMap::default();
+        match self {
+            Grouping::Packages => {
+                let mut packages = PackageCache::default();
+                for path in paths {
+                    if let Some(package) = packages.package_of(base_path, path)? {
+                        groups.insert(path.clone(), package);
+                    }
+                }
+            }
+            Grouping::Patterns(patterns) => {
+                for path in paths {
+                    if let Some((name, _)) = patterns
+                        .iter()
+                        .find(|(_, pattern)| matches_pattern(pattern, path))
+                    {
+                        groups.insert(path.clone(), Arc::from(name.as_str()));
+                    }
+                }
+            }
+        }
+        Ok(groups)
+    }
+}
+
+/// Packages of the directories looked up so far.
+#[derive(Default)]
+struct PackageCache {
+    packages_by_directory: HashMap<PathBuf, Option<Arc<str>>>,
+}
+
+impl PackageCache {
+    fn package_of(
+        &mut self,
+        base_path: &Path,
+        path: &Path,
+    ) -> Result<Option<Arc<str>>, WorkspaceError> {
+        let Some(directory) = path.parent() else {
+            return Ok(None);
+        };
+        if let Some(package) = self.packages_by_directory.get(directory) {
+            return Ok(package.clone());
+        }
+        let package = match read_package_json(&base_path.join(directory))? {
+            Some(package_json) => Some(match package_json.name {
+                Some(name) => Arc::from(name),
+                None if directory.as_os_str().is_empty() => Arc::from("./"),
+                None => Arc::from(format!("{}/", directory.to_string_lossy())),
+            }),
+            None => self.package_of(base_path, directory)?,
+        };
+        self.packages_by_directory
+            .insert(directory.to_path_buf(), package.clone());
+        Ok(package)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::test_dir::TestDir;
+
+    #[test]
+    fn test_group_by_package() {
+        let dir = TestDir::new(
+            "group-by-package",
+            &[
+                ("package.json", "{}"),
+                ("packages/a/package.json", r#"{ "name": "a" }"#),
+                ("packages/b/package.json", "{}"),
+            ],
+        );
+        let paths = ["packages/a/src/x.ts", "packages/b/y.ts", "scripts/z.js"]
+            .map(|path| Arc::from(Path::new(path)));
+        let groups = Grouping::new(&[])
+            .unwrap()
+            .group_paths(&dir, paths.iter())
+            .unwrap();
+        let group = |path: &str| groups[Path::new(path)].as_ref();
+        assert_eq!(group("packages/a/src/x.ts"), "a");
+        assert_eq!(group("packages/b/y.ts"), "packages/b/");
+        assert_eq!(group("scripts/z.js"), "./");
+    }
+
+    #[test]
+    fn test_group_by_pattern() {
+        let grouping = Grouping::new(&[
+            ("ui".to_owned(), "src/ui/**".to_owned()),
+            ("domain".to_owned(), "src/**".to_owned()),
+        ])
+        .unwrap();
+        let paths =
+            ["src/ui/button.tsx", "src/model.ts", "main.ts"].map(|path| Arc::from(Path::new(path)));
+        let groups = grouping.group_paths(Path::new("/"), paths.iter()).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[Path::new("src/ui/button.tsx")].as_ref(), "ui");
+        assert_eq!(groups[Path::new("src/model.ts")].as_ref(), "domain");
+    }
+}
@@ -0,0 +1,241 @@
+//! Architectural layer rules, declared by the `layers` key of the configuration such as:
+//!
+//! ```json
+//! {
+//!   "layers": [
+//!     { "name": "ui", "include": ["src/ui/**"], "mayImport": ["domain"] },
+//!     { "name": "infra", "include": ["src/infra/**"], "mayImport": ["domain"] },
+//!     { "name": "domain", "include": ["src/domain/**"] }
+//!   ]
+//! }
+//! ```
+//!
+//! A file belongs to the first layer with a matching pattern. Files may import files of their
+//! own layer, of the layers listed in `mayImport`, and files outside of any layer.
+
+use std::{
+    fmt::{self, Display},
+    io::{self, Write},
+    ops::Deref,
+    path::Path,
+    sync::Arc,
+};
+
+use camino::{FromPathError, Utf8Path};
+use petgraph::visit::{EdgeRef as _, IntoEdgeReferences as _};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    count,
+    entries::{parse_pattern, EntriesError},
+    groups::Grouping,
+    report::{import_locations, write_source_locations, SourceLocation},
+    source::SourceCache,
+    JsGraph,
+};
+use crate::hash::{HashMap, HashSet};
+
+/// The validated layers of the configuration.
+#[derive(Debug)]
+pub struct LayerRules<'a> {
+    layers: &'a [Layer],
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Layer {
+    pub name: String,
+    /// Glob patterns of the files in the layer, relative to the current directory.
+    pub include: Vec<String>,
+    /// Names of the other layers that the files of this layer may import.
+    #[serde(default)]
+    pub may_import: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum LayerError {
+    NoLayers,
+    Pattern(EntriesError),
+    DuplicateLayer(String),
+    UnknownLayer(String),
+}
+
+impl Display for LayerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayerError::NoLayers => write!(f, "the configuration declares no `layers`"),
+            LayerError::Pattern(err) => err.fmt(f),
+            LayerError::DuplicateLayer(name) => write!(f, "layer `{name}` is declared twice"),
+            LayerError::UnknownLayer(name) => write!(f, "unknown layer `{name}` in `mayImport`"),
+        }
+    }
+}
+
+impl From<EntriesError> for LayerError {
+    fn from(err: EntriesError) -> Self {
+        LayerError::Pattern(err)
+    }
+}
+
+/// An import between two layers that the rules don't allow.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Violation<'a> {
+    pub source: &'a Utf8Path,
+    pub target: &'a Utf8Path,
+    pub source_layer: String,
+    pub target_layer: String,
+    pub imports: Vec<SourceLocation>,
+}
+
+impl<'a> LayerRules<'a> {
+    /// Validates the `layers` of the configuration.
+    pub fn new(layers: &'a [Layer]) -> Result<Self, LayerError> {
+        if layers.is_empty() {
+            return Err(LayerError::NoLayers);
+        }
+        let mut names = HashSet::default();
+        for layer in layers {
+            if !names.insert(layer.name.as_str()) {
+                return Err(LayerError::DuplicateLayer(layer.name.clone()));
+            }
+        }
+        match layers
+            .iter()
+            .flat_map(|layer| &layer.may_import)
+            .find(|name| !names.contains(name.as_str()))
+        {
+            Some(unknown) => Err(LayerError::UnknownLayer(unknown.clone())),
+            None => Ok(Self { layers }),
+        }
+    }
+
+    /// Groups the files by layer, in the order of the rules.
+    pub fn grouping(&self) -> Result<Grouping, LayerError> {
+        let patterns = self
+            .layers
+            .iter()
+            .flat_map(|layer| {
+                layer
+                    .include
+                    .iter()
+                    .map(|pattern| Ok((layer.name.clone(), parse_pattern(pattern)?)))
+            })
+            .collect::<Result<Vec<_>, EntriesError>>()?;
+        Ok(Grouping::Patterns(patterns))
+    }
+
+    /// Returns the imports of `graph` that break the rules, sorted by source and target.
+    /// `layers_by_path` is the layer of each file, from [`Self::grouping`].
+    pub fn violations<'g>(
+        &self,
+        graph: &'g JsGraph,
+        layers_by_path: &HashMap<Arc<Path>, Arc<str>>,
+        sources: &mut SourceCache,
+    ) -> Result<Vec<Violation<'g>>, FromPathError> {
+        let allowed = self
+            .layers
+            .iter()
+            .flat_map(|layer| {
+                layer
+                    .may_import
+                    .iter()
+                    .map(|target| (layer.name.as_str(), target.as_str()))
+            })
+            .collect::<HashSet<_>>();
+
+        let path_graph = graph.dependency_graph.path_graph();
+        let mut violations = vec![];
+        for edge_ref in path_graph.edge_references() {
+            let source = &path_graph[edge_ref.source()];
+            let target = &path_graph[edge_ref.target()];
+            let (Some(source_layer), Some(target_layer)) =
+                (layers_by_path.get(source), layers_by_path.get(target))
+            else {
+                continue;
+            };
+            if source_layer == target_layer
+                || allowed.contains(&(source_layer.deref(), target_layer.deref()))
+            {
+                continue;
+            }
+            let source: &Utf8Path = source.deref().try_into()?;
+            violations.push(Violation {
+                source,
+                target: target.deref().try_into()?,
+                source_layer: source_layer.to_string(),
+                target_layer: target_layer.to_string(),
+                imports: import_locations(sources, source, edge_ref.weight()),
+            });
+        }
+        violations.sort_unstable_by_key(|violation| (violation.source, violation.target));
+        Ok(violations)
+    }
+}
+
+pub fn write_text(violations: &[Violation], stdout: &mut dyn Write) -> io::Result<()> {
+    if violations.is_empty() {
+        return writeln!(stdout, "Found no imports breaking the layer rules");
+    }
+    writeln!(
+        stdout,
+        "Found {} breaking the layer rules:",
+        count(violations.len(), "import")
+    )?;
+    for violation in violations {
+        writeln!(
+            stdout,
+            "  {} -> {} ({} may not import {})",
+            violation.source, violation.target, violation.source_layer, violation.target_layer
+        )?;
+        write_source_locations(stdout, violation.source, &violation.imports)?;
+    }
+    Ok(())
+}
+
+pub fn write_json(violations: &[Violation], stdout: &mut dyn Write) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *stdout, violations)?;
+    writeln!(stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::config::Config;
+
+    fn parse_layers(content: &str) -> Vec<Layer> {
+        serde_json::from_str::<Config>(content).unwrap().layers
+    }
+
+    #[test]
+    fn test_layer_rules() {
+        let layers = parse_layers(
+            r#"{ "layers": [
+                { "name": "ui", "include": ["ui/**"], "mayImport": ["domain"] },
+                { "name": "domain", "include": ["domain/**", "shared/**"] }
+            ] }"#,
+        );
+        let rules = LayerRules::new(&layers).unwrap();
+        assert_eq!(rules.layers.len(), 2);
+        assert!(rules.layers[1].may_import.is_empty());
+
+        assert!(matches!(LayerRules::new(&[]), Err(LayerError::NoLayers)));
+        let layers =
+            parse_layers(r#"{ "layers": [{ "name": "ui", "include": [], "mayImport": ["db"] }] }"#);
+        assert!(matches!(
+            LayerRules::new(&layers),
+            Err(LayerError::UnknownLayer(name)) if name == "db"
+        ));
+        let layers = parse_layers(
+            r#"{ "layers": [{ "name": "ui", "include": [] }, { "name": "ui", "include": [] }] }"#,
+        );
+        assert!(matches!(
+            LayerRules::new(&layers),
+            Err(LayerError::DuplicateLayer(name)) if name == "ui"
+        ));
+        assert!(serde_json::from_str::<Config>(
+            r#"{ "layers": [{ "name": "ui", "include": [], "allow": [] }] }"#
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,996 @@
+mod components;
+mod config;
+mod cut;
+mod diagnostics;
+mod entries;
+mod explain;
+mod export;
+mod groups;
+mod layers;
+mod packages;
+mod report;
+mod resolver;
+mod sarif;
+mod snapshot;
+mod source;
+#[cfg(test)]
+mod test_dir;
+mod workspace;
+use std::{
+    ffi::OsString,
+    fmt::{self, Display},
+    hash::Hash,
+    io::{self, Write},
+    ops::Deref,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::Arc,
+    time::Duration,
+};
+
+use camino::{FromPathError, Utf8Path, Utf8PathBuf};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+
+use components::cyclic_components;
+use config::{load_config, Config, ConfigError, IgnoredImports};
+use cut::Cut;
+use diagnostics::render_errors;
+use entries::{discover_entries, relative_to, EntriesError};
+use explain::write_import_path;
+use export::{Scope, Subgraph};
+use groups::{parse_group, Grouping};
+use layers::{LayerError, LayerRules};
+use packages::PackageCycles;
+use report::{import_locations, write_source_locations, Report};
+use resolver::ResolverOptions;
+use sarif::write_sarif;
+use snapshot::{
+    read_snapshot, write_snapshot, Snapshot, SnapshotEdge, SnapshotError, SNAPSHOT_VERSION,
+};
+use source::SourceCache;
+use workspace::{discover_workspace_packages, WorkspaceError};
+
+use crate::{
+    algorithms::{bounded_simple_cycles::CycleLimits, path_edges::TraversalSpace},
+    collect_deps::{collect_dependencies, DependencyGraphWithErrors, DiscoverDependency},
+    dep_graph::{DependencyGraph, ShortestCycles},
+    hash::HashSet,
+    js_resolver::{trace_barrel_imports, ImportKind, JsImport},
+    JsDiscoverDependency, OsFileSystem,
+};
+
+const DEFAULT_SNAPSHOT_PATH: &str = "cyclepath-snapshot.json";
+
+type JsDiscover = JsDiscoverDependency<OsFileSystem>;
+type JsEdge = <JsDiscover as DiscoverDependency>::Edge;
+type JsGraph = DependencyGraphWithErrors<JsEdge, <JsDiscover as DiscoverDependency>::Error>;
+
+/// Find import cycles in JavaScript and TypeScript projects
+#[derive(Parser, Debug)]
+#[command(name = "decycle", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    /// Configuration file, relative to the current directory [default: decycle.json, or the
+    /// `decycle` key of package.json]
+    #[arg(long, value_name = "PATH", global = true)]
+    config: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Exit with a non-zero code if an import not recorded in the snapshot is part of a cycle
+    Check(SnapshotArgs),
+    /// Write the imports that are part of cycles to the snapshot file
+    Snapshot(SnapshotArgs),
+    /// List the simple cycles in the dependency graph, with the imports that form them
+    Cycles(ReportArgs),
+    /// List the strongly connected components that contain cycles, largest first
+    Components(ComponentsArgs),
+    /// Suggest a small set of imports to remove to break all the cycles
+    ///
+    /// The imports are ranked by the number of cycles they are in.
+    Cut(CutArgs),
+    /// List the imports between packages that form cycles of packages, even without cycles of
+    /// files
+    Packages(PackagesArgs),
+    /// Exit with a non-zero code if an import goes between layers in a direction that the rules
+    /// don't allow
+    ///
+    /// The layers are declared by the `layers` key of the configuration, documented in
+    /// `src/cli/layers.rs`.
+    Layers(LayersArgs),
+    /// Print the dependency graph, or a part of it
+    Graph(ExportArgs),
+    /// Show how two files import each other, exiting with a non-zero code if they aren't in a
+    /// cycle
+    Explain(ExplainArgs),
+}
+
+#[derive(Args, Debug)]
+struct GraphArgs {
+    /// Entry files to start scanning from, relative to the current directory
+    entries: Vec<String>,
+    /// Glob pattern of additional entry files, such as `src/pages/**/*.tsx`
+    #[arg(long, value_name = "PATTERN")]
+    include: Vec<String>,
+    /// Glob pattern of entry files to skip
+    #[arg(long, value_name = "PATTERN")]
+    exclude: Vec<String>,
+    #[command(flatten)]
+    imports: ImportArgs,
+}
+
+/// Which imports make up the graph, and how they are resolved.
+#[derive(Args, Clone, Debug)]
+struct ImportArgs {
+    /// Leave out the imports of a file that are all dynamic `import()`, which only load it
+    /// lazily
+    #[arg(long)]
+    ignore_dynamic_imports: bool,
+    /// Point the imports of names re-exported by barrel files, such as an `index.ts` made of
+    /// `export * from './x'`, at the files that define them
+    #[arg(long)]
+    trace_barrels: bool,
+    /// Which imports make up the graph [default: runtime]
+    #[arg(long, value_enum)]
+    import_kinds: Option<ImportKinds>,
+    #[command(flatten)]
+    resolver: ResolverArgs,
+}
+
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+enum ImportKinds {
+    /// The imports that load the imported file at runtime
+    #[default]
+    Runtime,
+    /// The type-only imports, such as `import type`, which are erased when compiling
+    Type,
+    /// Both runtime and type-only imports
+    Both,
+}
+
+impl ImportKinds {
+    fn is_runtime(&self) -> bool {
+        *self == ImportKinds::Runtime
+    }
+}
+
+#[derive(Args, Clone, Debug)]
+struct ResolverArgs {
+    /// Also follow non-relative imports, such as tsconfig path aliases and workspace packages
+    #[arg(long)]
+    bare_specifiers: bool,
+    /// tsconfig.json to read path aliases from [default: tsconfig.json if it exists]
+    #[arg(long, value_name = "PATH")]
+    tsconfig: Option<Utf8PathBuf>,
+    /// Name of the directories of third-party packages, whose files are left out
+    /// [default: node_modules]
+    #[arg(long, value_name = "NAME")]
+    external_dir: Vec<String>,
+    /// Follow imports of the packages declared in the `workspaces` of package.json or in
+    /// pnpm-workspace.yaml to their source files rather than their build output. Implies
+    /// --bare-specifiers
+    #[arg(long)]
+    workspaces: bool,
+}
+
+#[derive(Args, Debug)]
+struct SnapshotArgs {
+    #[command(flatten)]
+    graph: GraphArgs,
+    /// Path of the snapshot file, relative to the current directory
+    /// [default: cyclepath-snapshot.json]
+    #[arg(long, value_name = "PATH")]
+    snapshot: Option<PathBuf>,
+    /// Only consider the imports in cycles that read the imported bindings while the importing
+    /// file is evaluated, which can fail at runtime
+    ///
+    /// Reads in functions count when the functions are called at the top level of the file,
+    /// directly or as callbacks. Bindings and functions are matched by name, regardless of
+    /// scoping, and constructors called with `new` aren't followed.
+    #[arg(long)]
+    runtime_only: bool,
+}
+
+#[derive(Args, Debug)]
+struct ReportArgs {
+    #[command(flatten)]
+    graph: GraphArgs,
+    #[command(flatten)]
+    limits: CycleLimitArgs,
+    /// Only list the cycles with an import that reads the imported bindings while the importing
+    /// file is evaluated, which can fail at runtime
+    ///
+    /// See `decycle check --help` for how the reads are found.
+    #[arg(long)]
+    runtime_only: bool,
+    /// [default: text]
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat>,
+}
+
+/// Limits on the search of simple cycles, whose number grows exponentially with the size of the
+/// tangles. Shorter cycles are found first.
+#[derive(Args, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+struct CycleLimitArgs {
+    /// Skip the cycles made of more imports than this [default: 10]
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    max_cycle_length: Option<u64>,
+    /// Stop after finding this many cycles [default: 100000]
+    #[arg(long, value_name = "N")]
+    max_cycles: Option<usize>,
+    /// Stop looking for cycles after this many seconds. Without it, the cycles found only depend
+    /// on the graph and the other limits
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<u64>,
+}
+
+/// Limits on the search of simple cycles applied when they aren't set, so that a large tangle
+/// can't stall the search. There is no default timeout, which would make the output depend on
+/// the speed of the machine.
+const DEFAULT_CYCLE_LIMITS: CycleLimits = CycleLimits {
+    max_length: Some(10),
+    max_count: Some(100_000),
+    timeout: None,
+};
+
+impl CycleLimitArgs {
+    /// The limits set by the flags, or else by `config`, or else [`DEFAULT_CYCLE_LIMITS`].
+    fn limits(&self, config: &CycleLimitArgs) -> CycleLimits {
+        CycleLimits {
+            max_length: self
+                .max_cycle_length
+                .or(config.max_cycle_length)
+                .map(|max_length| max_length as usize),
+            max_count: self.max_cycles.or(config.max_cycles),
+            timeout: self.timeout.or(config.timeout).map(Duration::from_secs),
+        }
+        .or(DEFAULT_CYCLE_LIMITS)
+    }
+}
+
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+enum OutputFormat {
+    #[default]
+    Text,
+    /// The schema is documented in `src/cli/report.rs`
+    Json,
+    /// SARIF 2.1.0, with a result for each import in a cycle
+    Sarif,
+}
+
+#[derive(Args, Debug)]
+struct ComponentsArgs {
+    #[command(flatten)]
+    graph: GraphArgs,
+    /// [default: text]
+    #[arg(long, value_enum)]
+    format: Option<ListFormat>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default)]
+enum ListFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Args, Debug)]
+struct CutArgs {
+    #[command(flatten)]
+    graph: GraphArgs,
+    /// Limits on the search of the cycles that each import is counted in
+    #[command(flatten)]
+    limits: CycleLimitArgs,
+    /// [default: text]
+    #[arg(long, value_enum)]
+    format: Option<ListFormat>,
+}
+
+#[derive(Args, Debug)]
+struct PackagesArgs {
+    #[command(flatten)]
+    graph: GraphArgs,
+    /// Group the files matching PATTERN under NAME instead of by their nearest package.json. A
+    /// file goes to the first matching group, and files matching none are left out
+    #[arg(long, value_name = "NAME=PATTERN", value_parser = parse_group)]
+    group: Vec<(String, String)>,
+    /// [default: text]
+    #[arg(long, value_enum)]
+    format: Option<ListFormat>,
+}
+
+#[derive(Args, Debug)]
+struct LayersArgs {
+    #[command(flatten)]
+    graph: GraphArgs,
+    /// [default: text]
+    #[arg(long, value_enum)]
+    format: Option<ListFormat>,
+}
+
+#[derive(Args, Debug)]
+struct ExportArgs {
+    #[command(flatten)]
+    graph: GraphArgs,
+    #[arg(long, value_enum, default_value_t)]
+    format: GraphFormat,
+    /// Only print the files that are part of a cycle
+    #[arg(long, conflicts_with = "around")]
+    cycles_only: bool,
+    /// Only print the files around this one
+    #[arg(long, value_name = "FILE")]
+    around: Option<PathBuf>,
+    /// How many imports away from the `--around` file to go, in either direction
+    #[arg(long, default_value_t = 1, requires = "around")]
+    depth: usize,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default)]
+enum GraphFormat {
+    #[default]
+    Text,
+    /// Graphviz DOT language
+    Dot,
+    /// Mermaid flowchart
+    Mermaid,
+}
+
+/// Only the files reachable from the two files are scanned, which covers all the import paths
+/// between them. The imports are otherwise taken into account like in the other commands.
+#[derive(Args, Debug)]
+struct ExplainArgs {
+    /// Importing file, relative to the current directory
+    from: String,
+    /// Imported file, relative to the current directory
+    to: String,
+    #[command(flatten)]
+    imports: ImportArgs,
+}
+
+/// The result of scanning the dependency graph from the entries.
+struct Scan {
+    entries: Vec<Utf8PathBuf>,
+    resolver: ResolverOptions,
+    import_kinds: ImportKinds,
+    trace_barrels: bool,
+    ignore_dynamic_imports: bool,
+    graph: JsGraph,
+    /// Imports left out of `graph` by an ignore comment.
+    ignored_by_comment: Vec<(Arc<Path>, Arc<Path>, JsImport)>,
+}
+
+#[derive(Debug)]
+enum Error {
+    Io(io::Error),
+    NonUtf8Path(FromPathError),
+    Entries(EntriesError),
+    Snapshot(SnapshotError),
+    Workspace(WorkspaceError),
+    Layers(LayerError),
+    Config(ConfigError),
+    NotInGraph(PathBuf),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => err.fmt(f),
+            Error::NonUtf8Path(err) => err.fmt(f),
+            Error::Entries(err) => err.fmt(f),
+            Error::Snapshot(err) => err.fmt(f),
+            Error::Workspace(err) => err.fmt(f),
+            Error::Layers(err) => err.fmt(f),
+            Error::Config(err) => err.fmt(f),
+            Error::NotInGraph(path) => {
+                write!(f, "{} is not reachable from the entries", path.display())
+            }
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<FromPathError> for Error {
+    fn from(err: FromPathError) -> Self {
+        Error::NonUtf8Path(err)
+    }
+}
+
+impl From<EntriesError> for Error {
+    fn from(err: EntriesError) -> Self {
+        Error::Entries(err)
+    }
+}
+
+impl From<SnapshotError> for Error {
+    fn from(err: SnapshotError) -> Self {
+        Error::Snapshot(err)
+    }
+}
+
+impl From<WorkspaceError> for Error {
+    fn from(err: WorkspaceError) -> Self {
+        Error::Workspace(err)
+    }
+}
+
+impl From<LayerError> for Error {
+    fn from(err: LayerError) -> Self {
+        Error::Layers(err)
+    }
+}
+
+impl From<ConfigError> for Error {
+    fn from(err: ConfigError) -> Self {
+        Error::Config(err)
+    }
+}
+
+/// Runs the command line interface with `args` (including the binary name) as if it was launched
+/// in `cwd`, writing the results to `stdout`. The arguments are taken as `OsString`s, which the
+/// parser rejects when they aren't valid UTF-8.
+pub fn run<T: Into<OsString> + Clone>(
+    args: impl IntoIterator<Item = T>,
+    cwd: &Path,
+    stdout: &mut dyn Write,
+) -> ExitCode {
+    let cli = match Cli::try_parse_from(args) {
+        Ok(cli) => cli,
+        Err(err) => {
+            if err.use_stderr() {
+                let _ = err.print();
+            } else {
+                let _ = write!(stdout, "{}", err.render());
+            }
+            return ExitCode::from(err.exit_code() as u8);
+        }
+    };
+    let result = load_config(cwd, cli.config.as_deref())
+        .map_err(Error::from)
+        .and_then(|config| cli.command.run(cwd, &config, stdout));
+    match result {
+        Ok(exit_code) => exit_code,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+impl Command {
+    fn run(&self, cwd: &Path, config: &Config, stdout: &mut dyn Write) -> Result<ExitCode, Error> {
+        match self {
+            Command::Check(args) => {
+                let scan = args.graph.scan(cwd, config)?;
+                let runtime_only = args.runtime_only || config.runtime_only;
+                let edges = js_cycle_edges(&scan.graph.dependency_graph, runtime_only)?;
+                let snapshot_path = cwd.join(args.snapshot_path(config));
+                let snapshot_edges = match read_snapshot(&snapshot_path)? {
+                    Some(read) => {
+                        if let Some(version) = read.migrated_from {
+                            eprintln!(
+                                "warning: the snapshot uses format version {version}, run `decycle snapshot` to upgrade it to version {SNAPSHOT_VERSION}"
+                            );
+                        } else if read.snapshot.entries != scan.entries
+                            || read.snapshot.resolver != scan.resolver
+                            || read.snapshot.import_kinds != scan.import_kinds
+                            || read.snapshot.trace_barrels != scan.trace_barrels
+                            || read.snapshot.ignore_dynamic_imports != scan.ignore_dynamic_imports
+                            || read.snapshot.runtime_only != runtime_only
+                        {
+                            eprintln!(
+                                "warning: the snapshot was taken with different entries, resolver options, --import-kinds, --trace-barrels, --ignore-dynamic-imports or --runtime-only"
+                            );
+                        }
+                        read.snapshot.edges
+                    }
+                    None => vec![],
+                };
+
+                let snapshot_edges = snapshot_edges
+                    .iter()
+                    .map(|edge| (edge.source.as_path(), edge.target.as_path()))
+                    .collect::<HashSet<_>>();
+                let new_edges = edges
+                    .iter()
+                    .filter(|(from, to, _)| !snapshot_edges.contains(&(*from, *to)))
+                    .collect::<Vec<_>>();
+                let current_edges = edges
+                    .iter()
+                    .map(|(from, to, _)| (*from, *to))
+                    .collect::<HashSet<_>>();
+                let mut removed_edges = snapshot_edges
+                    .into_iter()
+                    .filter(|edge| !current_edges.contains(edge))
+                    .collect::<Vec<_>>();
+                removed_edges.sort_unstable();
+
+                if !removed_edges.is_empty() {
+                    writeln!(
+                        stdout,
+                        "{} in the snapshot {} no longer in cycles, run `decycle snapshot` to remove {}:",
+                        count(removed_edges.len(), "import"),
+                        if removed_edges.len() == 1 { "is" } else { "are" },
+                        if removed_edges.len() == 1 { "it" } else { "them" }
+                    )?;
+                    for (from, to) in removed_edges {
+                        writeln!(stdout, "  {from} -> {to}")?;
+                    }
+                }
+                if new_edges.is_empty() {
+                    return Ok(ExitCode::SUCCESS);
+                }
+                writeln!(
+                    stdout,
+                    "Found {} in cycles:",
+                    count(new_edges.len(), "new import")
+                )?;
+                let mut sources = SourceCache::new(cwd);
+                let dependency_graph = &scan.graph.dependency_graph;
+                let mut cycles = dependency_graph.shortest_cycles();
+                for (from, to, imports) in new_edges {
+                    writeln!(stdout, "  {from} -> {to}")?;
+                    let cycle = shortest_file_cycle(dependency_graph, &mut cycles, from, to)?;
+                    writeln!(
+                        stdout,
+                        "    in a cycle of length {}: {}",
+                        cycle.len(),
+                        CycleDisplay(&cycle)
+                    )?;
+                    let locations = import_locations(&mut sources, from, *imports);
+                    write_source_locations(stdout, from, &locations)?;
+                }
+                Ok(ExitCode::FAILURE)
+            }
+            Command::Snapshot(args) => {
+                let scan = args.graph.scan(cwd, config)?;
+                let runtime_only = args.runtime_only || config.runtime_only;
+                let edges = js_cycle_edges(&scan.graph.dependency_graph, runtime_only)?;
+                let snapshot = Snapshot {
+                    version: SNAPSHOT_VERSION,
+                    entries: scan.entries.clone(),
+                    resolver: scan.resolver.clone(),
+                    import_kinds: scan.import_kinds,
+                    trace_barrels: scan.trace_barrels,
+                    ignore_dynamic_imports: scan.ignore_dynamic_imports,
+                    runtime_only,
+                    edges: edges
+                        .iter()
+                        .map(|(source, target, _)| SnapshotEdge {
+                            source: source.to_path_buf(),
+                            target: target.to_path_buf(),
+                        })
+                        .collect(),
+                };
+                let snapshot_path = args.snapshot_path(config);
+                write_snapshot(&cwd.join(snapshot_path), &snapshot)?;
+                writeln!(
+                    stdout,
+                    "Wrote {} in cycles to {}",
+                    count(edges.len(), "import"),
+                    snapshot_path.display()
+                )?;
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Cycles(args) => {
+                let scan = args.graph.scan(cwd, config)?;
+                let runtime_only = args.runtime_only || config.runtime_only;
+                let mut sources = SourceCache::new(cwd);
+                let format = args.format.or(config.format).unwrap_or_default();
+                if let OutputFormat::Sarif = format {
+                    // Only the imports in cycles are reported, so the cycles aren't enumerated
+                    let cycle_edges = report::cycle_edges(&scan.graph, runtime_only, &mut sources)?;
+                    write_sarif(&cycle_edges, stdout)?;
+                    return Ok(ExitCode::SUCCESS);
+                }
+                let report = Report::new(
+                    &scan.graph,
+                    &scan.ignored_by_comment,
+                    &args.limits.limits(&config.limits),
+                    runtime_only,
+                    &mut sources,
+                )?;
+                if let OutputFormat::Json = format {
+                    report.write_json(stdout)?;
+                } else {
+                    report.write_text(stdout)?;
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Components(args) => {
+                let scan = args.graph.scan(cwd, config)?;
+                let components = cyclic_components(&scan.graph.dependency_graph)?;
+                match list_format(args.format, config, "components")? {
+                    ListFormat::Text => components::write_text(&components, stdout)?,
+                    ListFormat::Json => components::write_json(&components, stdout)?,
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Cut(args) => {
+                let scan = args.graph.scan(cwd, config)?;
+                let cut = Cut::new(
+                    &scan.graph,
+                    &args.limits.limits(&config.limits),
+                    &mut SourceCache::new(cwd),
+                )?;
+                match list_format(args.format, config, "cut")? {
+                    ListFormat::Text => cut.write_text(stdout)?,
+                    ListFormat::Json => cut.write_json(stdout)?,
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Packages(args) => {
+                let grouping = Grouping::new(&args.group)?;
+                let scan = args.graph.scan(cwd, config)?;
+                let file_graph = &scan.graph.dependency_graph;
+                let groups = grouping.group_paths(cwd, file_graph.path_graph().node_weights())?;
+                let package_graph = file_graph.collapse(|path| groups.get(path).cloned());
+                let package_cycles = PackageCycles::new(file_graph, &package_graph)?;
+                match list_format(args.format, config, "packages")? {
+                    ListFormat::Text => package_cycles.write_text(stdout)?,
+                    ListFormat::Json => package_cycles.write_json(stdout)?,
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Layers(args) => {
+                let rules = LayerRules::new(&config.layers)?;
+                let grouping = rules.grouping()?;
+                let scan = args.graph.scan(cwd, config)?;
+                let layers_by_path = grouping
+                    .group_paths(cwd, scan.graph.dependency_graph.path_graph().node_weights())?;
+                let violations =
+                    rules.violations(&scan.graph, &layers_by_path, &mut SourceCache::new(cwd))?;
+                match list_format(args.format, config, "layers")? {
+                    ListFormat::Text => layers::write_text(&violations, stdout)?,
+                    ListFormat::Json => layers::write_json(&violations, stdout)?,
+                }
+                Ok(if violations.is_empty() {
+                    ExitCode::SUCCESS
+                } else {
+                    ExitCode::FAILURE
+                })
+            }
+            Command::Graph(args) => {
+                let scan = args.graph.scan(cwd, config)?;
+                let dependency_graph = &scan.graph.dependency_graph;
+                let scope = if let Some(around) = &args.around {
+                    let node = dependency_graph
+                        .node_index(&relative_to(cwd, around))
+                        .ok_or_else(|| Error::NotInGraph(around.clone()))?;
+                    Scope::Around {
+                        node,
+                        depth: args.depth,
+                    }
+                } else if args.cycles_only {
+                    Scope::Cycles
+                } else {
+                    Scope::All
+                };
+                let subgraph = Subgraph::new(dependency_graph, scope);
+                match args.format {
+                    GraphFormat::Text => subgraph.write_text(stdout)?,
+                    GraphFormat::Dot => subgraph.write_dot(stdout)?,
+                    GraphFormat::Mermaid => subgraph.write_mermaid(stdout)?,
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Explain(args) => {
+                let graph_args = GraphArgs {
+                    entries: vec![args.from.clone(), args.to.clone()],
+                    include: vec![],
+                    exclude: vec![],
+                    imports: args.imports.clone(),
+                };
+                // Only the entries given on the command line are scanned
+                let config = Config {
+                    entries: vec![],
+                    include: vec![],
+                    exclude: vec![],
+                    ..config.clone()
+                };
+                let scan = graph_args.scan(cwd, &config)?;
+                let dependency_graph = &scan.graph.dependency_graph;
+                let [from, to] = [&args.from, &args.to].map(|path| {
+                    let path = relative_to(cwd, Path::new(path));
+                    dependency_graph
+                        .node_index(&path)
+                        .ok_or(Error::NotInGraph(path))
+                });
+                let (from, to) = (from?, to?);
+                let mut sources = SourceCache::new(cwd);
+                let forward = write_import_path(&scan.graph, from, to, &mut sources, stdout)?;
+                let backward = write_import_path(&scan.graph, to, from, &mut sources, stdout)?;
+                Ok(if forward && backward {
+                    ExitCode::SUCCESS
+                } else {
+                    ExitCode::FAILURE
+                })
+            }
+        }
+    }
+}
+
+impl GraphArgs {
+    /// Scans the dependency graph with the options given on the command line, or else in
+    /// `config`.
+    fn scan(&self, cwd: &Path, config: &Config) -> Result<Scan, Error> {
+        let or_config = |args: &'_ Vec<String>, configured: &'_ Vec<String>| {
+            if args.is_empty() {
+                configured.clone()
+            } else {
+                args.clone()
+            }
+        };
+        let entries = discover_entries(
+            cwd,
+            &or_config(&self.entries, &config.entries),
+            &or_config(&self.include, &config.include),
+            &or_config(&self.exclude, &config.exclude),
+        )?
+        .into_iter()
+        .map(Utf8PathBuf::try_from)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| Error::NonUtf8Path(err.from_path_error()))?;
+        let ignored_imports = IgnoredImports::new(&config.ignore)?;
+        let resolver = self.imports.resolver.options(cwd, &config.resolver)?;
+        let mut resolve_options = resolver.to_resolve_options(cwd);
+        if resolver.workspaces {
+            for package in discover_workspace_packages(cwd)? {
+                resolve_options.alias.extend(package.aliases(cwd));
+            }
+        }
+        let trace_barrels = self.imports.trace_barrels || config.trace_barrels;
+        let mut js_discover_dependency =
+            JsDiscoverDependency::new(OsFileSystem::default(), resolve_options)
+                .with_bare_specifiers(resolver.to_bare_specifiers());
+        if trace_barrels {
+            js_discover_dependency = js_discover_dependency.with_file_exports();
+        }
+        let import_kinds = self
+            .imports
+            .import_kinds
+            .or(config.import_kinds)
+            .unwrap_or_default();
+        if import_kinds != ImportKinds::Runtime {
+            js_discover_dependency = js_discover_dependency.with_type_imports();
+        }
+        eprintln!("Scanning");
+        let mut graph = collect_dependencies(cwd, entries.iter(), &js_discover_dependency);
+        // Traced first so that the filters below see the files that define the imported names
+        if trace_barrels {
+            trace_barrel_imports(
+                &mut graph.dependency_graph,
+                cwd,
+                &js_discover_dependency.into_file_exports(),
+            );
+        }
+        let ignore_dynamic_imports =
+            self.imports.ignore_dynamic_imports || config.ignore_dynamic_imports;
+        let mut ignored_by_comment = vec![];
+        graph
+            .dependency_graph
+            .retain_edges(|source, target, imports| {
+                if ignored_imports.contains(source, target) {
+                    return false;
+                }
+                imports.retain(|import| {
+                    if import.ignore.is_none() {
+                        return true;
+                    }
+                    // Only the imports of the kinds in the graph are reported
+                    if import_kinds != ImportKinds::Type || import.kind == ImportKind::Type {
+                        ignored_by_comment.push((source.clone(), target.clone(), import.clone()));
+                    }
+                    false
+                });
+                !imports.is_empty()
+            });
+        // The files only imported through ignored imports are left out along with their errors
+        let roots = entries
+            .iter()
+            .filter_map(|entry| graph.dependency_graph.node_index(entry.as_std_path()))
+            .collect::<Vec<_>>();
+        for path in graph.dependency_graph.remove_unreachable(roots) {
+            graph.errors_by_path.remove(&path);
+        }
+        graph.dependency_graph.retain_edges(|_, _, imports| {
+            // Runtime imports are still followed to scan the files, but left out of the graph
+            if import_kinds == ImportKinds::Type {
+                imports.retain(|import| import.kind == ImportKind::Type);
+            }
+            if ignore_dynamic_imports {
+                imports
+                    .iter()
+                    .any(|import| import.kind != ImportKind::Dynamic)
+            } else {
+                !imports.is_empty()
+            }
+        });
+        if !graph.errors_by_path.is_empty() {
+            let mut rendered = String::new();
+            let summary = render_errors(
+                &graph.errors_by_path,
+                &mut SourceCache::new(cwd),
+                &mut rendered,
+            )
+            .map_err(io::Error::other)?;
+            eprint!("{rendered}");
+            eprintln!(
+                "Found {summary} in {}",
+                count(graph.errors_by_path.len(), "file")
+            );
+        }
+        Ok(Scan {
+            entries,
+            resolver,
+            import_kinds,
+            trace_barrels,
+            ignore_dynamic_imports,
+            graph,
+            ignored_by_comment,
+        })
+    }
+}
+
+impl ResolverArgs {
+    /// The `configured` options overridden by the flags.
+    fn options(&self, cwd: &Path, configured: &ResolverOptions) -> Result<ResolverOptions, Error> {
+        let mut options = configured.clone();
+        options.bare_specifiers |= self.bare_specifiers;
+        if let Some(tsconfig) = &self.tsconfig {
+            options.tsconfig = Some(tsconfig.clone());
+        }
+        if !self.external_dir.is_empty() {
+            options.external_directories = self.external_dir.clone();
+        }
+        options.workspaces |= self.workspaces;
+        // Workspace packages are imported with bare specifiers
+        options.bare_specifiers |= options.workspaces;
+        options.check_bare_specifiers()?;
+        if options.bare_specifiers && options.tsconfig.is_none() {
+            let default_tsconfig = Utf8PathBuf::from("tsconfig.json");
+            options.tsconfig = cwd
+                .join(&default_tsconfig)
+                .is_file()
+                .then_some(default_tsconfig);
+        }
+        Ok(options)
+    }
+}
+
+impl SnapshotArgs {
+    fn snapshot_path<'a>(&'a self, config: &'a Config) -> &'a Path {
+        self.snapshot
+            .as_deref()
+            .or(config.snapshot.as_deref())
+            .unwrap_or(Path::new(DEFAULT_SNAPSHOT_PATH))
+    }
+}
+
+/// The list format set by the flag, or else in `config`, which may be one that `command`
+/// doesn't support.
+fn list_format(
+    format: Option<ListFormat>,
+    config: &Config,
+    command: &'static str,
+) -> Result<ListFormat, ConfigError> {
+    match (format, config.format) {
+        (Some(format), _) => Ok(format),
+        (None, None | Some(OutputFormat::Text)) => Ok(ListFormat::Text),
+        (None, Some(OutputFormat::Json)) => Ok(ListFormat::Json),
+        (None, Some(OutputFormat::Sarif)) => Err(ConfigError::UnsupportedFormat(command)),
+    }
+}
+
+/// Returns the edges that are part of at least one cycle, sorted by their endpoints.
+fn cycle_edges<E, N: ?Sized + Hash + Ord>(
+    dependency_graph: &DependencyGraph<E, N>,
+) -> Vec<(&N, &N, &E)> {
+    let path_graph = dependency_graph.path_graph();
+    let mut space = TraversalSpace::new(path_graph);
+    let mut edges = space
+        .find_edges_in_cycles()
+        .into_iter()
+        .map(|edge_id| {
+            let (from_id, to_id) = path_graph.edge_endpoints(edge_id).unwrap();
+            (
+                path_graph[from_id].deref(),
+                path_graph[to_id].deref(),
+                &path_graph[edge_id],
+            )
+        })
+        .collect::<Vec<_>>();
+    edges.sort_unstable_by_key(|(from, to, _)| (*from, *to));
+    edges
+}
+
+/// Returns the imports in cycles like [`cycle_edges`], only the ones used at top level with
+/// `runtime_only`.
+fn js_cycle_edges(
+    dependency_graph: &DependencyGraph<JsEdge>,
+    runtime_only: bool,
+) -> Result<Vec<(&Utf8Path, &Utf8Path, &JsEdge)>, FromPathError> {
+    cycle_edges(dependency_graph)
+        .into_iter()
+        .filter(|(_, _, imports)| !runtime_only || is_used_at_top_level(imports))
+        .map(|(from, to, imports)| Ok((from.try_into()?, to.try_into()?, imports)))
+        .collect()
+}
+
+/// Whether one of the imports reads the imported bindings while the importing file is
+/// evaluated.
+fn is_used_at_top_level(imports: &JsEdge) -> bool {
+    imports.iter().any(|import| import.used_at_top_level)
+}
+
+/// Returns the nodes of one of the shortest cycles going through the edge from `from` to `to`,
+/// found with `cycles` from `dependency_graph`.
+///
+/// # Panics
+///
+/// If the edge isn't part of a cycle: the callers only pass edges listed by [`cycle_edges`].
+fn shortest_cycle<'a, E, N: ?Sized + Hash + Ord>(
+    dependency_graph: &DependencyGraph<E, N>,
+    cycles: &mut ShortestCycles<'a, E, N>,
+    from: &N,
+    to: &N,
+) -> Vec<&'a N> {
+    let from = dependency_graph
+        .node_index(from)
+        .expect("edge source in graph");
+    let to = dependency_graph
+        .node_index(to)
+        .expect("edge target in graph");
+    cycles
+        .find(from, to)
+        .expect("edge in a cycle")
+        .into_iter()
+        .map(Deref::deref)
+        .collect()
+}
+
+/// Returns the files of one of the shortest cycles going through the import from `from` to `to`
+/// like [`shortest_cycle`].
+fn shortest_file_cycle<'a, E>(
+    dependency_graph: &DependencyGraph<E>,
+    cycles: &mut ShortestCycles<'a, E, Path>,
+    from: &Utf8Path,
+    to: &Utf8Path,
+) -> Result<Vec<&'a Utf8Path>, FromPathError> {
+    shortest_cycle(
+        dependency_graph,
+        cycles,
+        from.as_std_path(),
+        to.as_std_path(),
+    )
+    .into_iter()
+    .map(TryInto::try_into)
+    .collect()
+}
+
+/// Formats `count` followed by `noun`, with an `s` unless the count is 1.
+fn count(count: usize, noun: &str) -> String {
+    format!("{count} {noun}{}", if count == 1 { "" } else { "s" })
+}
+
+/// Displays the files of a cycle as `a -> b -> a`.
+struct CycleDisplay<'a, T>(&'a [T]);
+
+impl<T: Display> Display for CycleDisplay<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for path in self.0 {
+            write!(f, "{path} -> ")?;
+        }
+        write!(f, "{}", self.0[0])
+    }
+}
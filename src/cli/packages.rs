@@ -0,0 +1,120 @@
+use std::{
+    io::{self, Write},
+    ops::Deref,
+};
+
+use camino::{FromPathError, Utf8Path};
+use petgraph::stable_graph::EdgeIndex;
+use serde::Serialize;
+
+use super::{count, cycle_edges, shortest_cycle, CycleDisplay, JsEdge};
+use crate::dep_graph::DependencyGraph;
+
+/// Number of file imports shown for each import between packages.
+const EXAMPLE_COUNT: usize = 3;
+
+/// Imports between packages that are part of a cycle of packages.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageCycles<'a> {
+    /// Sorted by source and target.
+    pub cycle_edges: Vec<PackageEdge<'a>>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageEdge<'a> {
+    pub source: &'a str,
+    pub target: &'a str,
+    /// The packages of one of the shortest cycles going through this edge, starting with
+    /// `source`.
+    pub shortest_cycle: Vec<&'a str>,
+    /// Number of file imports from `source` to `target`.
+    pub imports: usize,
+    /// The first few of these file imports, sorted.
+    pub examples: Vec<FileEdge<'a>>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FileEdge<'a> {
+    pub source: &'a Utf8Path,
+    pub target: &'a Utf8Path,
+}
+
+impl<'a> PackageCycles<'a> {
+    /// `package_graph` is `file_graph` collapsed into packages.
+    pub fn new(
+        file_graph: &'a DependencyGraph<JsEdge>,
+        package_graph: &'a DependencyGraph<Vec<EdgeIndex>, str>,
+    ) -> Result<Self, FromPathError> {
+        let path_graph = file_graph.path_graph();
+        let mut cycles = package_graph.shortest_cycles();
+        let cycle_edges = cycle_edges(package_graph)
+            .into_iter()
+            .map(|(source, target, file_edges)| {
+                let mut examples = file_edges
+                    .iter()
+                    .map(|edge| {
+                        let (from, to) = path_graph.edge_endpoints(*edge).unwrap();
+                        Ok(FileEdge {
+                            source: path_graph[from].deref().try_into()?,
+                            target: path_graph[to].deref().try_into()?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, FromPathError>>()?;
+                examples.sort_unstable_by_key(|edge| (edge.source, edge.target));
+                examples.truncate(EXAMPLE_COUNT);
+                Ok(PackageEdge {
+                    source,
+                    target,
+                    shortest_cycle: shortest_cycle(package_graph, &mut cycles, source, target),
+                    imports: file_edges.len(),
+                    examples,
+                })
+            })
+            .collect::<Result<Vec<_>, FromPathError>>()?;
+        Ok(Self { cycle_edges })
+    }
+
+    pub fn write_text(&self, stdout: &mut dyn Write) -> io::Result<()> {
+        if self.cycle_edges.is_empty() {
+            return writeln!(stdout, "Found no cycles between packages");
+        }
+        writeln!(
+            stdout,
+            "Found {} package {} in cycles:",
+            self.cycle_edges.len(),
+            if self.cycle_edges.len() == 1 {
+                "dependency"
+            } else {
+                "dependencies"
+            }
+        )?;
+        for edge in &self.cycle_edges {
+            writeln!(stdout, "  {} -> {}", edge.source, edge.target)?;
+            writeln!(
+                stdout,
+                "    in a cycle of length {}: {}",
+                edge.shortest_cycle.len(),
+                CycleDisplay(&edge.shortest_cycle)
+            )?;
+            for example in &edge.examples {
+                writeln!(stdout, "    {} -> {}", example.source, example.target)?;
+            }
+            if edge.imports > edge.examples.len() {
+                writeln!(
+                    stdout,
+                    "    and {} more",
+                    count(edge.imports - edge.examples.len(), "import")
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn write_json(&self, stdout: &mut dyn Write) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *stdout, self)?;
+        writeln!(stdout)
+    }
+}
@@ -0,0 +1,460 @@
+//! The machine-readable report printed by `decycle cycles --format json`.
+//!
+//! All paths are relative to the directory decycle runs in. Offsets are UTF-8 byte offsets into
+//! the file, while lines and columns are 1-based and count characters, so that they can be used
+//! both for slicing the source and for jumping to the location in an editor. Additive changes
+//! keep [`REPORT_VERSION`]; anything else bumps it.
+
+use std::{
+    io::{self, Write},
+    ops::Deref,
+    path::Path,
+    sync::Arc,
+};
+
+use camino::{FromPathError, Utf8Path};
+use oxc_span::Span;
+use serde::Serialize;
+
+use super::{
+    count, is_used_at_top_level, js_cycle_edges, shortest_file_cycle, source::SourceCache, JsEdge,
+    JsGraph,
+};
+use crate::{
+    algorithms::bounded_simple_cycles::{Completeness, CycleLimits},
+    js_resolver::{JsDiscoverDependencyError, JsImport},
+};
+
+pub const REPORT_VERSION: u32 = 1;
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Report<'a> {
+    /// Version of the schema of this report.
+    pub version: u32,
+    pub stats: GraphStats,
+    /// Imports that are part of at least one cycle, sorted by source and target.
+    pub cycle_edges: Vec<Edge<'a>>,
+    /// Simple cycles, each as the sequence of imports that forms it, shortest first. The target
+    /// of the last import is the source of the first one.
+    pub cycles: Vec<Vec<Edge<'a>>>,
+    /// Whether `cycles` lists all the simple cycles, or which limit stopped the search.
+    pub cycle_search: CycleSearch,
+    /// Files that couldn't be fully scanned, sorted by path.
+    pub errors: Vec<FileErrors<'a>>,
+    /// Imports left out by a `decycle-ignore-next-line` comment, sorted by source and location.
+    pub ignored_imports: Vec<IgnoredImport<'a>>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphStats {
+    /// Number of files reached from the entries.
+    pub files: usize,
+    /// Number of importing/imported file pairs. Several imports of the same file count once.
+    pub imports: usize,
+    pub cycle_edges: usize,
+    pub cycles: usize,
+    pub files_with_errors: usize,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CycleSearch {
+    Complete,
+    /// Cycles longer than `--max-cycle-length` were skipped.
+    MaxLength,
+    /// There are more cycles than `--max-cycles`.
+    MaxCount,
+    Timeout,
+}
+
+impl From<Completeness> for CycleSearch {
+    fn from(completeness: Completeness) -> Self {
+        match completeness {
+            Completeness::Complete => CycleSearch::Complete,
+            Completeness::MaxLength => CycleSearch::MaxLength,
+            Completeness::MaxCount => CycleSearch::MaxCount,
+            Completeness::Timeout => CycleSearch::Timeout,
+        }
+    }
+}
+
+/// The imports from the `source` file to the `target` file.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Edge<'a> {
+    pub source: &'a Utf8Path,
+    pub target: &'a Utf8Path,
+    /// Locations of the import specifiers in `source`.
+    pub imports: Vec<SourceLocation>,
+    /// Whether one of the imports reads the imported bindings while `source` is evaluated, so
+    /// that the cycle can fail at runtime.
+    pub used_at_top_level: bool,
+    /// For the imports in `cycleEdges`, the files of one of the shortest cycles that goes
+    /// through them, starting with `source`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shortest_cycle: Option<Vec<&'a Utf8Path>>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceLocation {
+    pub start: u32,
+    pub end: u32,
+    pub line: usize,
+    pub column: usize,
+    /// Position right after the end of the location.
+    pub end_line: usize,
+    pub end_column: usize,
+    /// The source lines containing the import statement, or the location for errors, joined
+    /// with their surrounding whitespace trimmed.
+    pub text: String,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct IgnoredImport<'a> {
+    pub source: &'a Utf8Path,
+    pub target: &'a Utf8Path,
+    /// Absent when the source file can no longer be read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<SourceLocation>,
+    /// The text following the directive in the comment, if any.
+    pub reason: Option<&'a str>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FileErrors<'a> {
+    pub path: &'a Utf8Path,
+    pub errors: Vec<FileError>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FileError {
+    pub kind: FileErrorKind,
+    pub message: String,
+    /// Where the error is in the file, absent for read errors and some parse errors.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<SourceLocation>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FileErrorKind {
+    Read,
+    Parse,
+    Resolve,
+    NonLiteralImport,
+}
+
+/// Resolves `spans` in the file at `path` to locations, each with the span whose lines make up
+/// its text. An unreadable file has no locations.
+pub fn source_locations(
+    sources: &mut SourceCache,
+    path: &Utf8Path,
+    spans: impl IntoIterator<Item = (Span, Span)>,
+) -> Vec<SourceLocation> {
+    let source = match sources.get(path.as_std_path()) {
+        Ok(ok) => ok,
+        Err(err) => {
+            eprintln!("warning: failed to read {path}: {err}");
+            return vec![];
+        }
+    };
+    spans
+        .into_iter()
+        .map(|(span, text_span)| {
+            let line_column = source.line_column(span.start);
+            let end_line_column = source.line_column(span.end);
+            SourceLocation {
+                start: span.start,
+                end: span.end,
+                line: line_column.line,
+                column: line_column.column,
+                end_line: end_line_column.line,
+                end_column: end_line_column.column,
+                text: source
+                    .lines(text_span)
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            }
+        })
+        .collect()
+}
+
+/// Resolves the specifiers of `imports` in the file at `path` to locations, with the lines of
+/// their statements as text.
+pub fn import_locations<'a>(
+    sources: &mut SourceCache,
+    path: &Utf8Path,
+    imports: impl IntoIterator<Item = &'a JsImport>,
+) -> Vec<SourceLocation> {
+    source_locations(
+        sources,
+        path,
+        imports
+            .into_iter()
+            .map(|import| (import.span, import.statement)),
+    )
+}
+
+/// Writes `path:line:column: text` for each location, so that editors can jump to it.
+pub fn write_source_locations(
+    stdout: &mut dyn Write,
+    path: &Utf8Path,
+    locations: &[SourceLocation],
+) -> io::Result<()> {
+    for SourceLocation {
+        line, column, text, ..
+    } in locations
+    {
+        writeln!(stdout, "    {path}:{line}:{column}: {text}")?;
+    }
+    Ok(())
+}
+
+impl<'a> Edge<'a> {
+    fn new(
+        sources: &mut SourceCache,
+        source: &'a Utf8Path,
+        target: &'a Utf8Path,
+        imports: &JsEdge,
+    ) -> Self {
+        Self {
+            source,
+            target,
+            imports: import_locations(sources, source, imports),
+            used_at_top_level: is_used_at_top_level(imports),
+            shortest_cycle: None,
+        }
+    }
+}
+
+fn file_errors(
+    sources: &mut SourceCache,
+    path: &Utf8Path,
+    error: &JsDiscoverDependencyError,
+) -> Vec<FileError> {
+    let (parse_errors, resolve_errors, non_literal_imports) = match error {
+        JsDiscoverDependencyError::FileReadError(err) => {
+            return vec![FileError {
+                kind: FileErrorKind::Read,
+                message: err.to_string(),
+                location: None,
+            }]
+        }
+        JsDiscoverDependencyError::ParseOrResolveError {
+            parse_errors,
+            resolve_errors,
+            non_literal_imports,
+        } => (parse_errors, resolve_errors, non_literal_imports),
+    };
+    let errors = parse_errors
+        .iter()
+        .map(|diagnostic| {
+            let span = diagnostic.labels.iter().flatten().next().map(|label| {
+                Span::new(label.offset() as u32, (label.offset() + label.len()) as u32)
+            });
+            (FileErrorKind::Parse, diagnostic.message.clone(), span)
+        })
+        .chain(
+            resolve_errors
+                .iter()
+                .map(|(err, span)| (FileErrorKind::Resolve, err.to_string(), Some(*span))),
+        )
+        .chain(non_literal_imports.iter().map(|span| {
+            (
+                FileErrorKind::NonLiteralImport,
+                "import with a non-literal specifier can't be followed".to_owned(),
+                Some(*span),
+            )
+        }))
+        .collect::<Vec<_>>();
+    let mut locations = source_locations(
+        sources,
+        path,
+        errors.iter().filter_map(|e| e.2).map(|span| (span, span)),
+    )
+    .into_iter();
+    errors
+        .into_iter()
+        .map(|(kind, message, span)| FileError {
+            kind,
+            message,
+            location: span.and_then(|_| locations.next()),
+        })
+        .collect()
+}
+
+/// Returns the imports that are part of at least one cycle, with one of the shortest cycles
+/// through each, like the `cycleEdges` of [`Report`]. With `runtime_only`, only the imports used
+/// at top level are listed.
+pub fn cycle_edges<'a>(
+    graph: &'a JsGraph,
+    runtime_only: bool,
+    sources: &mut SourceCache,
+) -> Result<Vec<Edge<'a>>, FromPathError> {
+    let dependency_graph = &graph.dependency_graph;
+    let mut cycles = dependency_graph.shortest_cycles();
+    js_cycle_edges(dependency_graph, runtime_only)?
+        .into_iter()
+        .map(|(source, target, imports)| {
+            Ok(Edge {
+                shortest_cycle: Some(shortest_file_cycle(
+                    dependency_graph,
+                    &mut cycles,
+                    source,
+                    target,
+                )?),
+                ..Edge::new(sources, source, target, imports)
+            })
+        })
+        .collect()
+}
+
+impl<'a> Report<'a> {
+    /// With `runtime_only`, only the cycles with an import used at top level are listed, and only
+    /// these imports are counted as cycle edges.
+    pub fn new(
+        graph: &'a JsGraph,
+        ignored_by_comment: &'a [(Arc<Path>, Arc<Path>, JsImport)],
+        limits: &CycleLimits,
+        runtime_only: bool,
+        sources: &mut SourceCache,
+    ) -> Result<Self, FromPathError> {
+        let dependency_graph = &graph.dependency_graph;
+        let path_graph = dependency_graph.path_graph();
+
+        let cycle_edges = cycle_edges(graph, runtime_only, sources)?;
+
+        // Filtered during the search so that the limits apply to the cycles that are listed
+        let (cycles, completeness) = dependency_graph.find_cycles(limits, |edges| {
+            !runtime_only || edges.iter().any(|imports| is_used_at_top_level(imports))
+        });
+        let mut cycles = cycles
+            .map(|cycle| {
+                let mut cycle = cycle
+                    .map(|(from, to, imports)| {
+                        Ok((from.deref().try_into()?, to.deref().try_into()?, imports))
+                    })
+                    .collect::<Result<Vec<(&Utf8Path, &Utf8Path, &JsEdge)>, FromPathError>>()?;
+                // Start each cycle at its smallest path so that the output is stable
+                let min_index = (0..cycle.len()).min_by_key(|i| cycle[*i].0).unwrap();
+                cycle.rotate_left(min_index);
+                Ok(cycle)
+            })
+            .collect::<Result<Vec<_>, FromPathError>>()?;
+        cycles.sort_unstable_by(|a, b| {
+            a.len()
+                .cmp(&b.len())
+                .then_with(|| a.iter().map(|edge| edge.0).cmp(b.iter().map(|edge| edge.0)))
+        });
+        let cycles = cycles
+            .into_iter()
+            .map(|cycle| {
+                cycle
+                    .into_iter()
+                    .map(|(source, target, imports)| Edge::new(sources, source, target, imports))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut errors = graph
+            .errors_by_path
+            .iter()
+            .map(|(path, error)| {
+                let path: &Utf8Path = path.deref().try_into()?;
+                Ok(FileErrors {
+                    path,
+                    errors: file_errors(sources, path, error),
+                })
+            })
+            .collect::<Result<Vec<_>, FromPathError>>()?;
+        errors.sort_unstable_by_key(|file_errors| file_errors.path);
+
+        let mut ignored_imports = ignored_by_comment
+            .iter()
+            .map(|(source, target, import)| {
+                let source: &Utf8Path = source.deref().try_into()?;
+                Ok(IgnoredImport {
+                    source,
+                    target: target.deref().try_into()?,
+                    location: import_locations(sources, source, [import]).pop(),
+                    reason: import
+                        .ignore
+                        .as_ref()
+                        .and_then(|comment| comment.reason.as_deref()),
+                })
+            })
+            .collect::<Result<Vec<_>, FromPathError>>()?;
+        ignored_imports.sort_unstable_by_key(|import| {
+            (
+                import.source,
+                import.location.as_ref().map(|location| location.start),
+            )
+        });
+
+        Ok(Self {
+            version: REPORT_VERSION,
+            stats: GraphStats {
+                files: path_graph.node_count(),
+                imports: path_graph.edge_count(),
+                cycle_edges: cycle_edges.len(),
+                cycles: cycles.len(),
+                files_with_errors: errors.len(),
+            },
+            cycle_edges,
+            cycles,
+            cycle_search: completeness.into(),
+            errors,
+            ignored_imports,
+        })
+    }
+
+    pub fn write_text(&self, stdout: &mut dyn Write) -> io::Result<()> {
+        for cycle in &self.cycles {
+            for edge in cycle {
+                write!(stdout, "{} -> ", edge.source)?;
+            }
+            writeln!(stdout, "{}", cycle[0].source)?;
+            for edge in cycle {
+                write_source_locations(stdout, edge.source, &edge.imports)?;
+            }
+        }
+        write!(stdout, "Found {}", count(self.cycles.len(), "cycle"))?;
+        match self.cycle_search {
+            CycleSearch::Complete => writeln!(stdout),
+            CycleSearch::MaxLength => writeln!(stdout, ", longer ones were skipped"),
+            CycleSearch::MaxCount => writeln!(stdout, ", stopped at the maximum count"),
+            CycleSearch::Timeout => writeln!(stdout, ", stopped at the timeout"),
+        }?;
+        if self.ignored_imports.is_empty() {
+            return Ok(());
+        }
+        writeln!(
+            stdout,
+            "Ignored {} with comments:",
+            count(self.ignored_imports.len(), "import")
+        )?;
+        for import in &self.ignored_imports {
+            write!(stdout, "  {} -> {}", import.source, import.target)?;
+            match import.reason {
+                Some(reason) => writeln!(stdout, ": {reason}")?,
+                None => writeln!(stdout)?,
+            }
+            write_source_locations(stdout, import.source, import.location.as_slice())?;
+        }
+        Ok(())
+    }
+
+    pub fn write_json(&self, stdout: &mut dyn Write) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *stdout, self)?;
+        writeln!(stdout)
+    }
+}
@@ -0,0 +1,105 @@
+use std::{collections::BTreeMap, path::Path};
+
+use camino::Utf8PathBuf;
+use oxc_resolver::{AliasValue, ResolveOptions, TsconfigOptions, TsconfigReferences};
+use serde::{Deserialize, Serialize};
+
+use super::config::ConfigError;
+use crate::js_resolver::BareSpecifiers;
+
+/// The subset of [`ResolveOptions`] that decycle exposes. It is recorded in snapshots, so every
+/// field has to be serializable. Fields missing from older snapshots take their default value.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct ResolverOptions {
+    pub extensions: Vec<String>,
+    /// Conditions matched against the `exports` and `imports` fields of package.json.
+    pub conditions: Vec<String>,
+    /// Specifiers replaced by another specifier, or by a path when it starts with `./` or `../`.
+    /// A key ending with `$` only matches the exact specifier.
+    pub alias: BTreeMap<String, String>,
+    /// Whether non-relative specifiers, such as path aliases and workspace packages, are
+    /// resolved.
+    pub bare_specifiers: bool,
+    /// tsconfig.json whose `paths` are used to resolve bare specifiers, relative to the current
+    /// directory.
+    pub tsconfig: Option<Utf8PathBuf>,
+    /// Bare specifiers resolving to a file inside a directory with one of these names are left
+    /// out as third-party packages.
+    pub external_directories: Vec<String>,
+    /// Whether imports of workspace packages resolve to their source files. Implies
+    /// `bare_specifiers`.
+    pub workspaces: bool,
+}
+
+impl Default for ResolverOptions {
+    fn default() -> Self {
+        Self {
+            extensions: [".js", ".jsx", ".ts", ".tsx", ".node", ".json"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            conditions: vec![],
+            alias: BTreeMap::new(),
+            bare_specifiers: false,
+            tsconfig: None,
+            external_directories: vec!["node_modules".to_owned()],
+            workspaces: false,
+        }
+    }
+}
+
+impl ResolverOptions {
+    pub fn to_resolve_options(&self, cwd: &Path) -> ResolveOptions {
+        ResolveOptions {
+            extensions: self.extensions.clone(),
+            condition_names: self.conditions.clone(),
+            alias: self
+                .alias
+                .iter()
+                .map(|(specifier, replacement)| {
+                    let replacement =
+                        if replacement.starts_with("./") || replacement.starts_with("../") {
+                            cwd.join(replacement).to_string_lossy().into_owned()
+                        } else {
+                            replacement.clone()
+                        };
+                    (specifier.clone(), vec![AliasValue::Path(replacement)])
+                })
+                .collect(),
+            tsconfig: self.tsconfig.as_ref().map(|tsconfig| TsconfigOptions {
+                config_file: cwd.join(tsconfig),
+                references: TsconfigReferences::Auto,
+            }),
+            builtin_modules: self.bare_specifiers,
+            ..Default::default()
+        }
+    }
+
+    /// Fails if an option that only applies to bare specifiers is set without `bare_specifiers`.
+    pub fn check_bare_specifiers(&self) -> Result<(), ConfigError> {
+        if self.bare_specifiers {
+            return Ok(());
+        }
+        let option = if !self.alias.is_empty() {
+            "alias"
+        } else if self.tsconfig.is_some() {
+            "tsconfig"
+        } else if self.external_directories != Self::default().external_directories {
+            "externalDirectories"
+        } else {
+            return Ok(());
+        };
+        Err(ConfigError::RequiresBareSpecifiers(option))
+    }
+
+    pub fn to_bare_specifiers(&self) -> BareSpecifiers {
+        if self.bare_specifiers {
+            BareSpecifiers::Resolve {
+                external_directories: self.external_directories.clone(),
+            }
+        } else {
+            BareSpecifiers::Skip
+        }
+    }
+}
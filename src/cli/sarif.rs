@@ -0,0 +1,167 @@
+//! [SARIF 2.1.0](https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html) output, with
+//! one result for each import that is part of a cycle. Only the properties decycle fills in are
+//! modelled.
+
+use std::io::{self, Write};
+
+use camino::Utf8Path;
+use serde::Serialize;
+
+use super::{report::Edge, CycleDisplay};
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const CYCLE_RULE_ID: &str = "import-cycle";
+/// Locations are relative to the directory decycle runs in, which the consumer maps to the
+/// checkout.
+const SRCROOT: &str = "%SRCROOT%";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Log<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: [Run<'a>; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run<'a> {
+    tool: Tool,
+    /// Columns in [`Edge`] locations count characters.
+    column_kind: &'static str,
+    results: Vec<SarifResult<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver {
+    name: &'static str,
+    version: &'static str,
+    rules: [Rule; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    id: &'static str,
+    short_description: Message,
+    full_description: Message,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult<'a> {
+    rule_id: &'static str,
+    level: &'static str,
+    message: Message,
+    locations: [Location<'a>; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location<'a> {
+    physical_location: PhysicalLocation<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation<'a> {
+    artifact_location: ArtifactLocation<'a>,
+    region: Region,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactLocation<'a> {
+    uri: &'a Utf8Path,
+    uri_base_id: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+}
+
+/// Writes a result for each import of the `cycle_edges`.
+pub fn write_sarif(cycle_edges: &[Edge], stdout: &mut dyn Write) -> io::Result<()> {
+    let results = cycle_edges
+        .iter()
+        .flat_map(|edge| {
+            edge.imports.iter().map(|import| SarifResult {
+                rule_id: CYCLE_RULE_ID,
+                level: "error",
+                message: Message {
+                    text: match &edge.shortest_cycle {
+                        Some(cycle) => format!(
+                            "The import of `{}` is part of an import cycle: {}.",
+                            edge.target,
+                            CycleDisplay(cycle)
+                        ),
+                        None => format!(
+                            "The import of `{}` is part of an import cycle.",
+                            edge.target
+                        ),
+                    },
+                },
+                locations: [Location {
+                    physical_location: PhysicalLocation {
+                        artifact_location: ArtifactLocation {
+                            uri: edge.source,
+                            uri_base_id: SRCROOT,
+                        },
+                        region: Region {
+                            start_line: import.line,
+                            start_column: import.column,
+                            end_line: import.end_line,
+                            end_column: import.end_column,
+                        },
+                    },
+                }],
+            })
+        })
+        .collect();
+    let log = Log {
+        schema: SCHEMA,
+        version: "2.1.0",
+        runs: [Run {
+            tool: Tool {
+                driver: Driver {
+                    name: "decycle",
+                    version: env!("CARGO_PKG_VERSION"),
+                    rules: [Rule {
+                        id: CYCLE_RULE_ID,
+                        short_description: Message {
+                            text: "Import cycle".to_owned(),
+                        },
+                        full_description: Message {
+                            text: "The imported file directly or indirectly imports the importing \
+                                   file back."
+                                .to_owned(),
+                        },
+                    }],
+                },
+            },
+            column_kind: "unicodeCodePoints",
+            results,
+        }],
+    };
+    serde_json::to_writer_pretty(&mut *stdout, &log)?;
+    writeln!(stdout)
+}
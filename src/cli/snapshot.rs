@@ -0,0 +1,197 @@
+use std::{
+    fmt::{self, Display},
+    io,
+    path::Path,
+};
+
+use camino::Utf8PathBuf;
+use serde::{Deserialize, Serialize};
+
+use super::{resolver::ResolverOptions, ImportKinds};
+
+/// Version of the snapshot format written by this version of decycle. Bump it whenever the
+/// format changes, and teach [`read_snapshot`] to migrate the older versions.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// The imports that are allowed to be in cycles, along with the options they were found with.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Snapshot {
+    pub version: u32,
+    pub entries: Vec<Utf8PathBuf>,
+    pub resolver: ResolverOptions,
+    /// Which imports made up the graph.
+    #[serde(default, skip_serializing_if = "ImportKinds::is_runtime")]
+    pub import_kinds: ImportKinds,
+    /// Whether imports were traced through barrel files.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub trace_barrels: bool,
+    /// Whether the imports that are all dynamic were left out.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ignore_dynamic_imports: bool,
+    /// Whether only the imports used at top level were recorded.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub runtime_only: bool,
+    pub edges: Vec<SnapshotEdge>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(deny_unknown_fields)]
+pub struct SnapshotEdge {
+    pub source: Utf8PathBuf,
+    pub target: Utf8PathBuf,
+}
+
+/// A snapshot read from disk.
+pub struct ReadSnapshot {
+    pub snapshot: Snapshot,
+    /// The version the snapshot was migrated from, if it was written in an older format.
+    pub migrated_from: Option<u32>,
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(io::Error),
+    Parse(serde_json::Error),
+    UnsupportedVersion(u64),
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(err) => write!(f, "failed to read the snapshot: {err}"),
+            SnapshotError::Parse(err) => write!(f, "invalid snapshot: {err}"),
+            SnapshotError::UnsupportedVersion(version) => write!(
+                f,
+                "snapshot format version {version} is not supported by this version of decycle \
+                 (expected at most {SNAPSHOT_VERSION})"
+            ),
+        }
+    }
+}
+
+/// Reads the snapshot at `path`, or returns `None` if it doesn't exist.
+///
+/// Version 0 is the unversioned format: a bare array of `[source, target]` pairs.
+pub fn read_snapshot(path: &Path) -> Result<Option<ReadSnapshot>, SnapshotError> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(ok) => ok,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(SnapshotError::Io(err)),
+    };
+    let value =
+        serde_json::from_str::<serde_json::Value>(&content).map_err(SnapshotError::Parse)?;
+    if value.is_array() {
+        let edges = serde_json::from_value::<Vec<(Utf8PathBuf, Utf8PathBuf)>>(value)
+            .map_err(SnapshotError::Parse)?;
+        return Ok(Some(ReadSnapshot {
+            snapshot: Snapshot {
+                version: SNAPSHOT_VERSION,
+                entries: vec![],
+                resolver: ResolverOptions::default(),
+                import_kinds: ImportKinds::Runtime,
+                trace_barrels: false,
+                ignore_dynamic_imports: false,
+                runtime_only: false,
+                edges: edges
+                    .into_iter()
+                    .map(|(source, target)| SnapshotEdge { source, target })
+                    .collect(),
+            },
+            migrated_from: Some(0),
+        }));
+    }
+    match value.get("version").and_then(serde_json::Value::as_u64) {
+        Some(version) if version == u64::from(SNAPSHOT_VERSION) => {}
+        Some(version) => return Err(SnapshotError::UnsupportedVersion(version)),
+        None => {
+            return Err(SnapshotError::Parse(serde::de::Error::missing_field(
+                "version",
+            )))
+        }
+    }
+    let snapshot = serde_json::from_value::<Snapshot>(value).map_err(SnapshotError::Parse)?;
+    Ok(Some(ReadSnapshot {
+        snapshot,
+        migrated_from: None,
+    }))
+}
+
+pub fn write_snapshot(path: &Path, snapshot: &Snapshot) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = std::fs::File::create(path)?;
+    serde_json::to_writer_pretty(file, snapshot)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::test_dir::TestDir;
+
+    fn read_str(name: &str, content: &str) -> Result<Option<ReadSnapshot>, SnapshotError> {
+        let dir = TestDir::new(&format!("snapshot-{name}"), &[("snapshot.json", content)]);
+        read_snapshot(&dir.join("snapshot.json"))
+    }
+
+    #[test]
+    fn test_read_missing_snapshot() {
+        assert!(read_snapshot("/non-existent/snapshot.json".as_ref())
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_migrate_unversioned_snapshot() {
+        let read = read_str("unversioned", r#"[["a.js", "b.js"], ["b.js", "a.js"]]"#)
+            .unwrap()
+            .unwrap();
+        assert_eq!(read.migrated_from, Some(0));
+        assert_eq!(read.snapshot.version, SNAPSHOT_VERSION);
+        assert_eq!(
+            read.snapshot.edges,
+            vec![
+                SnapshotEdge {
+                    source: "a.js".into(),
+                    target: "b.js".into()
+                },
+                SnapshotEdge {
+                    source: "b.js".into(),
+                    target: "a.js".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_default_resolver_options() {
+        let read = read_str(
+            "default-resolver",
+            r#"{ "version": 1, "entries": ["a.js"], "resolver": { "extensions": [".js"] }, "edges": [] }"#,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(read.migrated_from, None);
+        assert_eq!(
+            read.snapshot.resolver,
+            ResolverOptions {
+                extensions: vec![".js".to_owned()],
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_read_unsupported_version() {
+        assert!(matches!(
+            read_str("future", r#"{ "version": 1000, "edges": {} }"#),
+            Err(SnapshotError::UnsupportedVersion(1000))
+        ));
+        assert!(matches!(
+            read_str("no-version", r#"{ "edges": [] }"#),
+            Err(SnapshotError::Parse(_))
+        ));
+    }
+}
@@ -0,0 +1,112 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use oxc_span::Span;
+
+use crate::hash::HashMap;
+
+/// A 1-based line and column in a source file. Columns count characters, not bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineColumn {
+    pub line: usize,
+    pub column: usize,
+}
+
+pub struct SourceFile {
+    content: Arc<str>,
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    pub fn new(content: String) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(content.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        Self {
+            content: content.into(),
+            line_starts,
+        }
+    }
+
+    pub fn content(&self) -> &Arc<str> {
+        &self.content
+    }
+
+    fn line_index(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|start| *start <= offset) - 1
+    }
+
+    pub fn line_column(&self, offset: u32) -> LineColumn {
+        let offset = (offset as usize).min(self.content.len());
+        let line_index = self.line_index(offset);
+        let line_start = self.line_starts[line_index];
+        LineColumn {
+            line: line_index + 1,
+            column: self.content[line_start..offset].chars().count() + 1,
+        }
+    }
+
+    /// Returns the lines covered by `span`, without the trailing line break.
+    pub fn lines(&self, span: Span) -> &str {
+        let start = (span.start as usize).min(self.content.len());
+        let end = (span.end as usize).clamp(start, self.content.len());
+        let line_start = self.line_starts[self.line_index(start)];
+        let line_end = self.content[end..]
+            .find('\n')
+            .map_or(self.content.len(), |index| end + index);
+        self.content[line_start..line_end].trim_end_matches('\r')
+    }
+}
+
+/// Lazily reads and indexes the source files referred to by a report.
+pub struct SourceCache {
+    base_path: PathBuf,
+    files: HashMap<PathBuf, SourceFile>,
+}
+
+impl SourceCache {
+    pub fn new(base_path: &Path) -> Self {
+        Self {
+            base_path: base_path.to_path_buf(),
+            files: HashMap::default(),
+        }
+    }
+
+    /// Returns the file at `path`, relative to the base path.
+    pub fn get(&mut self, path: &Path) -> io::Result<&SourceFile> {
+        if !self.files.contains_key(path) {
+            let content = std::fs::read_to_string(self.base_path.join(path))?;
+            self.files
+                .insert(path.to_path_buf(), SourceFile::new(content));
+        }
+        Ok(&self.files[path])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_column() {
+        let content = "import a from './a'\r\n// é\nimport './b'";
+        let file = SourceFile::new(content.to_owned());
+        assert_eq!(file.line_column(0), LineColumn { line: 1, column: 1 });
+        assert_eq!(
+            file.line_column(14),
+            LineColumn {
+                line: 1,
+                column: 15
+            }
+        );
+        assert_eq!(file.line_column(21), LineColumn { line: 2, column: 1 });
+        assert_eq!(file.line_column(27), LineColumn { line: 3, column: 1 });
+        assert_eq!(file.line_column(34), LineColumn { line: 3, column: 8 });
+        assert_eq!(file.lines(Span::new(14, 19)), "import a from './a'");
+        assert_eq!(file.lines(Span::new(34, 39)), "import './b'");
+        assert_eq!(file.lines(Span::new(14, 34)), content);
+    }
+}
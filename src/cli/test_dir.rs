@@ -0,0 +1,39 @@
+//! Temporary directories for the unit tests, which unlike the integration tests don't get
+//! `CARGO_TARGET_TMPDIR`.
+
+use std::{
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+
+/// A directory holding the files of a test, removed when dropped. Its name includes the process
+/// id so that concurrent test runs don't write to the same directory.
+pub struct TestDir(PathBuf);
+
+impl TestDir {
+    pub fn new(name: &str, files: &[(&str, &str)]) -> Self {
+        let dir = std::env::temp_dir().join(format!("decycle-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (path, content) in files {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        Self(dir)
+    }
+}
+
+impl Deref for TestDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,251 @@
+use std::{
+    fmt::{self, Display},
+    io,
+    path::{Path, PathBuf},
+};
+
+use indexmap::IndexSet;
+use oxc_resolver::AliasValue;
+use serde::Deserialize;
+
+use super::entries::{glob_relative, matches_pattern, parse_pattern, EntriesError};
+
+/// Files tried in order as the source entry of a package without a `source` field.
+const SOURCE_ENTRIES: &[&str] = &[
+    "src/index.ts",
+    "src/index.tsx",
+    "src/index.js",
+    "src/index.jsx",
+];
+
+#[derive(Debug)]
+pub enum WorkspaceError {
+    Read(PathBuf, io::Error),
+    PackageJson(PathBuf, serde_json::Error),
+    PnpmWorkspace(serde_yaml::Error),
+    Pattern(EntriesError),
+}
+
+impl Display for WorkspaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkspaceError::Read(path, err) => {
+                write!(f, "failed to read {}: {err}", path.display())
+            }
+            WorkspaceError::PackageJson(path, err) => {
+                write!(f, "invalid {}: {err}", path.display())
+            }
+            WorkspaceError::PnpmWorkspace(err) => write!(f, "invalid pnpm-workspace.yaml: {err}"),
+            WorkspaceError::Pattern(err) => err.fmt(f),
+        }
+    }
+}
+
+impl From<EntriesError> for WorkspaceError {
+    fn from(err: EntriesError) -> Self {
+        WorkspaceError::Pattern(err)
+    }
+}
+
+/// The fields of package.json that decycle reads.
+#[derive(Deserialize, Debug, Default)]
+pub struct PackageJson {
+    pub name: Option<String>,
+    /// Entry file of the package sources, a convention of bundlers such as Parcel and microbundle.
+    pub source: Option<String>,
+    pub workspaces: Option<Workspaces>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum Workspaces {
+    Patterns(Vec<String>),
+    /// The Yarn classic format, which can also list packages not to hoist.
+    Object {
+        #[serde(default)]
+        packages: Vec<String>,
+    },
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct PnpmWorkspace {
+    #[serde(default)]
+    packages: Vec<String>,
+}
+
+/// Reads the package.json in `directory`, if there is one.
+pub fn read_package_json(directory: &Path) -> Result<Option<PackageJson>, WorkspaceError> {
+    let path = directory.join("package.json");
+    let content = match std::fs::read_to_string(&path) {
+        Ok(ok) => ok,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(WorkspaceError::Read(path, err)),
+    };
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|err| WorkspaceError::PackageJson(path, err))
+}
+
+/// A package of the workspace, with paths relative to the workspace root.
+#[derive(Debug, PartialEq, Eq)]
+pub struct WorkspacePackage {
+    pub name: String,
+    pub directory: PathBuf,
+    /// The file that imports of the package itself resolve to instead of its build output, if
+    /// there is one.
+    pub source_entry: Option<PathBuf>,
+}
+
+impl WorkspacePackage {
+    /// Resolver aliases mapping the package name to its source entry, and its subpaths to the
+    /// package directory.
+    pub fn aliases(&self, base_path: &Path) -> Vec<(String, Vec<AliasValue>)> {
+        let alias_value =
+            |path: &Path| AliasValue::Path(base_path.join(path).to_string_lossy().into_owned());
+        let mut aliases = vec![];
+        if let Some(source_entry) = &self.source_entry {
+            aliases.push((format!("{}$", self.name), vec![alias_value(source_entry)]));
+        }
+        aliases.push((self.name.clone(), vec![alias_value(&self.directory)]));
+        aliases
+    }
+}
+
+/// Returns the packages declared by the `workspaces` field of package.json and by
+/// pnpm-workspace.yaml in `base_path`, sorted by directory. Patterns starting with `!` exclude
+/// directories.
+pub fn discover_workspace_packages(
+    base_path: &Path,
+) -> Result<Vec<WorkspacePackage>, WorkspaceError> {
+    let mut patterns = match read_package_json(base_path)?.and_then(|root| root.workspaces) {
+        Some(Workspaces::Patterns(patterns)) => patterns,
+        Some(Workspaces::Object { packages }) => packages,
+        None => vec![],
+    };
+    let pnpm_workspace_path = base_path.join("pnpm-workspace.yaml");
+    match std::fs::read_to_string(&pnpm_workspace_path) {
+        Ok(content) => {
+            let pnpm_workspace = serde_yaml::from_str::<Option<PnpmWorkspace>>(&content)
+                .map_err(WorkspaceError::PnpmWorkspace)?
+                .unwrap_or_default();
+            patterns.extend(pnpm_workspace.packages);
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => return Err(WorkspaceError::Read(pnpm_workspace_path, err)),
+    }
+
+    let (exclude, include): (Vec<&String>, Vec<&String>) = patterns
+        .iter()
+        .partition(|pattern| pattern.starts_with('!'));
+    let exclude = exclude
+        .into_iter()
+        .map(|pattern| parse_pattern(pattern.trim_start_matches('!')))
+        .collect::<Result<Vec<_>, EntriesError>>()?;
+    let mut directories = IndexSet::<PathBuf>::default();
+    for pattern in include {
+        for directory in glob_relative(base_path, pattern.trim_end_matches('/'))? {
+            if base_path.join(&directory).is_dir()
+                && !exclude
+                    .iter()
+                    .any(|pattern| matches_pattern(pattern, &directory))
+            {
+                directories.insert(directory);
+            }
+        }
+    }
+    directories.sort_unstable();
+
+    let mut packages = vec![];
+    for directory in directories {
+        let Some(PackageJson {
+            name: Some(name),
+            source,
+            ..
+        }) = read_package_json(&base_path.join(&directory))?
+        else {
+            continue;
+        };
+        let source_entry = source
+            .map(|source| directory.join(source))
+            .into_iter()
+            .chain(SOURCE_ENTRIES.iter().map(|entry| directory.join(entry)))
+            .find(|path| base_path.join(path).is_file());
+        packages.push(WorkspacePackage {
+            name,
+            directory,
+            source_entry,
+        });
+    }
+    Ok(packages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::test_dir::TestDir;
+
+    #[test]
+    fn test_discover_workspace_packages() {
+        let dir = TestDir::new(
+            "workspace-discover",
+            &[
+                (
+                    "package.json",
+                    r#"{ "workspaces": ["packages/*", "!packages/skipped"] }"#,
+                ),
+                ("pnpm-workspace.yaml", "packages:\n  - 'apps/*'\n"),
+                (
+                    "packages/a/package.json",
+                    r#"{ "name": "a", "source": "lib/main.ts" }"#,
+                ),
+                ("packages/a/lib/main.ts", ""),
+                (
+                    "packages/b/package.json",
+                    r#"{ "name": "@scope/b", "main": "dist/index.js" }"#,
+                ),
+                ("packages/b/src/index.tsx", ""),
+                ("packages/skipped/package.json", r#"{ "name": "skipped" }"#),
+                ("packages/unnamed/package.json", "{}"),
+                ("packages/no-package-json/index.js", ""),
+                ("apps/web/package.json", r#"{ "name": "web" }"#),
+            ],
+        );
+        assert_eq!(
+            discover_workspace_packages(&dir).unwrap(),
+            vec![
+                WorkspacePackage {
+                    name: "web".to_owned(),
+                    directory: "apps/web".into(),
+                    source_entry: None,
+                },
+                WorkspacePackage {
+                    name: "a".to_owned(),
+                    directory: "packages/a".into(),
+                    source_entry: Some("packages/a/lib/main.ts".into()),
+                },
+                WorkspacePackage {
+                    name: "@scope/b".to_owned(),
+                    directory: "packages/b".into(),
+                    source_entry: Some("packages/b/src/index.tsx".into()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_yarn_classic_workspaces() {
+        let dir = TestDir::new(
+            "workspace-yarn-classic",
+            &[
+                (
+                    "package.json",
+                    r#"{ "workspaces": { "packages": ["pkg"], "nohoist": ["**/x"] } }"#,
+                ),
+                ("pkg/package.json", r#"{ "name": "pkg" }"#),
+            ],
+        );
+        let packages = discover_workspace_packages(&dir).unwrap();
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].name, "pkg");
+    }
+}
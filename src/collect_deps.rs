@@ -11,6 +11,7 @@ use rayon::iter::{ParallelBridge, ParallelIterator};
 pub trait DiscoverDependency: Send + Sync {
     type Edge: Send;
     type Error: Send;
+    #[allow(clippy::type_complexity)]
     fn discover_dependencies(
         &self,
         path: &Path,
@@ -39,27 +40,28 @@ pub fn collect_dependencies<D: DiscoverDependency>(
     let (deps_cx, deps_rx) = mpsc::channel::<DependencyInfo<D::Edge, D::Error>>();
     let (work_cx, work_rx) = mpsc::channel::<PathBuf>();
 
+    // Entries are inserted upfront so that an entry imported by another one isn't scanned twice
+    let mut dep_graph = DependencyGraph::<D::Edge>::default();
     let mut remaining = 0u32;
     for path in paths {
-        work_cx.send(base_path.join(path).into()).unwrap();
-        remaining += 1;
+        let path = base_path.join(path);
+        let relative_path = Arc::<Path>::from(pathdiff::diff_paths(&path, base_path).unwrap());
+        if dep_graph.get_path_index_or_insert(&relative_path).1 {
+            work_cx.send(path).unwrap();
+            remaining += 1;
+        }
+    }
+    if remaining == 0 {
+        return DependencyGraphWithErrors {
+            dependency_graph: dep_graph,
+            errors_by_path: HashMap::default(),
+        };
     }
 
-    let (_, dep_graph) = rayon::join(
-        move || {
-            work_rx.into_iter().par_bridge().for_each(move |path| {
-                let (dependencies, error) = dep_discoverer.discover_dependencies(&path);
-                deps_cx
-                    .send(DependencyInfo {
-                        path,
-                        dependencies,
-                        error,
-                    })
-                    .unwrap();
-            })
-        },
-        move || {
-            let mut dep_graph = DependencyGraph::<D::Edge>::default();
+    std::thread::scope(|scope| {
+        // The graph is built on a dedicated thread: the rayon workers below block on `work_rx`,
+        // so with a single-threaded pool a rayon task here would never get scheduled.
+        let dep_graph = scope.spawn(move || {
             let mut errors_by_path = HashMap::<Arc<Path>, D::Error>::default();
             for DependencyInfo {
                 path,
@@ -93,9 +95,19 @@ pub fn collect_dependencies<D: DiscoverDependency>(
                 dependency_graph: dep_graph,
                 errors_by_path,
             }
-        },
-    );
-    dep_graph
+        });
+        work_rx.into_iter().par_bridge().for_each(move |path| {
+            let (dependencies, error) = dep_discoverer.discover_dependencies(&path);
+            deps_cx
+                .send(DependencyInfo {
+                    path,
+                    dependencies,
+                    error,
+                })
+                .unwrap();
+        });
+        dep_graph.join().unwrap()
+    })
 }
 
 #[cfg(test)]
@@ -103,9 +115,8 @@ mod tests {
 
     use super::*;
     use crate::hash::{HashMap, HashSet};
-    struct TestDiscoverDependency(
-        HashMap<&'static Path, (Vec<(&'static Path, &'static str)>, Option<&'static str>)>,
-    );
+    type TestDependencies = (Vec<(&'static Path, &'static str)>, Option<&'static str>);
+    struct TestDiscoverDependency(HashMap<&'static Path, TestDependencies>);
 
     impl DiscoverDependency for TestDiscoverDependency {
         type Edge = &'static str;
@@ -117,7 +128,7 @@ mod tests {
         ) -> (Vec<(PathBuf, Self::Edge)>, Option<Self::Error>) {
             let (deps, err) = &self.0[path];
             (
-                deps.into_iter()
+                deps.iter()
                     .map(|(dep_path, edge)| (dep_path.to_path_buf(), *edge))
                     .collect(),
                 *err,
@@ -175,4 +186,39 @@ mod tests {
             .collect()
         )
     }
+
+    #[test]
+    fn test_collect_dependencies_multiple_entries() {
+        let test_discover_dep = TestDiscoverDependency({
+            let mut map = HashMap::default();
+            map.insert(p("/a"), (vec![(p("/b"), "a-b")], Some("a error")));
+            map.insert(p("/b"), (vec![(p("/a"), "b-a")], Some("b error")));
+            map
+        });
+        let result = collect_dependencies(
+            "/".as_ref(),
+            [ap("b"), ap("a"), ap("b")].into_iter(),
+            &test_discover_dep,
+        );
+        assert_eq!(result.errors_by_path.len(), 2);
+        result.dependency_graph.assert_consistency();
+        assert_eq!(
+            result.dependency_graph.edges().collect::<HashSet<_>>(),
+            [(p("a"), p("b"), &"a-b"), (p("b"), p("a"), &"b-a")]
+                .into_iter()
+                .collect()
+        );
+    }
+
+    #[test]
+    fn test_collect_dependencies_no_entries() {
+        let test_discover_dep = TestDiscoverDependency(HashMap::default());
+        let result = collect_dependencies(
+            "/".as_ref(),
+            std::iter::empty::<&Path>(),
+            &test_discover_dep,
+        );
+        assert_eq!(result.dependency_graph.paths().count(), 0);
+        assert!(result.errors_by_path.is_empty());
+    }
 }
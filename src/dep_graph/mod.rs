@@ -1,31 +1,48 @@
 use derive_where::derive_where;
 
-use std::{ops::Deref, path::Path, sync::Arc};
+use std::{hash::Hash, path::Path, sync::Arc};
 
-use crate::{algorithms::johnson_simple_cycles::find_simple_cycles, hash::HashMap};
-use petgraph::stable_graph::{NodeIndex, StableDiGraph};
+use crate::{
+    algorithms::{
+        bounded_simple_cycles::{
+            find_bounded_simple_cycles, BoundedCycles, Completeness, CycleLimits,
+        },
+        johnson_simple_cycles::find_simple_cycles,
+        path_edges::TraversalSpace,
+    },
+    hash::HashMap,
+};
+use petgraph::{
+    algo::kosaraju_scc,
+    stable_graph::{EdgeIndex, NodeIndex, StableDiGraph},
+    visit::{Dfs, EdgeRef as _, IntoEdgeReferences as _, NodeIndexable as _, VisitMap as _},
+};
 
+/// A graph of files, or of groups of files such as packages when `N` is `str`.
 #[derive(Debug)]
 #[derive_where(Default)]
-pub struct DependencyGraph<E> {
-    path_graph: StableDiGraph<Arc<Path>, E>,
-    node_indices_by_path: HashMap<Arc<Path>, NodeIndex>,
+pub struct DependencyGraph<E, N: ?Sized = Path> {
+    path_graph: StableDiGraph<Arc<N>, E>,
+    node_indices_by_path: HashMap<Arc<N>, NodeIndex>,
 }
 
-impl<E> DependencyGraph<E> {
-    pub fn path_graph(&self) -> &StableDiGraph<Arc<Path>, E> {
+impl<E, N: ?Sized + Hash + Ord> DependencyGraph<E, N> {
+    pub fn path_graph(&self) -> &StableDiGraph<Arc<N>, E> {
         &self.path_graph
     }
-    // pub fn node_indices_by_path(&self) -> &HashMap<Arc<Path>, NodeIndex> {
-    //     &self.node_indices_by_path
-    // }
+    pub fn node_index(&self, path: &N) -> Option<NodeIndex> {
+        self.node_indices_by_path.get(path).copied()
+    }
     #[cfg(test)]
-    pub fn paths<'a>(&'a self) -> impl Iterator<Item = &'a Path> {
-        self.node_indices_by_path.keys().map(|p| p.deref())
+    pub fn paths(&self) -> impl Iterator<Item = &N> {
+        self.node_indices_by_path.keys().map(|p| p.as_ref())
     }
 
     #[cfg(test)]
-    pub fn assert_consistency(&self) {
+    pub fn assert_consistency(&self)
+    where
+        N: std::fmt::Debug,
+    {
         assert_eq!(
             self.path_graph.node_count(),
             self.node_indices_by_path.len()
@@ -36,35 +53,388 @@ impl<E> DependencyGraph<E> {
     }
 
     #[cfg(test)]
-    pub fn edges<'a>(&'a self) -> impl Iterator<Item = (&'a Path, &'a Path, &'a E)> {
-        use petgraph::visit::{EdgeRef as _, IntoEdgeReferences as _};
+    pub fn edges(&self) -> impl Iterator<Item = (&N, &N, &E)> {
         self.path_graph.edge_references().map(|edge_ref| {
             (
-                self.path_graph[edge_ref.source()].deref(),
-                self.path_graph[edge_ref.target()].deref(),
+                self.path_graph[edge_ref.source()].as_ref(),
+                self.path_graph[edge_ref.target()].as_ref(),
                 edge_ref.weight(),
             )
         })
     }
 
-    pub fn get_path_index_or_insert(&mut self, path: &Arc<Path>) -> (NodeIndex, bool) {
-        let mut newly_inserted = false;
-        let node_index = *self
-            .node_indices_by_path
-            .entry_ref(path.deref())
-            .or_insert_with(|| {
-                newly_inserted = true;
-                self.path_graph.add_node(path.clone())
-            });
-        (node_index, newly_inserted)
+    pub fn get_path_index_or_insert(&mut self, path: &Arc<N>) -> (NodeIndex, bool) {
+        if let Some(node_index) = self.node_index(path) {
+            return (node_index, false);
+        }
+        let node_index = self.path_graph.add_node(path.clone());
+        self.node_indices_by_path.insert(path.clone(), node_index);
+        (node_index, true)
     }
     pub fn add_edge(&mut self, from: NodeIndex, to: NodeIndex, edge: E) {
         self.path_graph.add_edge(from, to, edge);
     }
 
-    // To do: return edges (source span) along with paths
-    pub fn find_cycles<'a>(&'a self) -> impl Iterator<Item = impl Iterator<Item = &'a Arc<Path>>> {
-        let cycles = find_simple_cycles(&self.path_graph);
-        cycles.map(|cycle| cycle.into_iter().map(|index| &self.path_graph[index]))
+    /// Returns the edge from `from` to `to`, adding it with the default value if there is none.
+    pub fn edge_mut_or_insert_default(&mut self, from: NodeIndex, to: NodeIndex) -> &mut E
+    where
+        E: Default,
+    {
+        let edge = match self.path_graph.find_edge(from, to) {
+            Some(edge) => edge,
+            None => self.path_graph.add_edge(from, to, E::default()),
+        };
+        &mut self.path_graph[edge]
+    }
+
+    /// Removes the edges for which `keep` returns false, which may also modify the edge. Their
+    /// nodes stay in the graph.
+    pub fn retain_edges(&mut self, mut keep: impl FnMut(&Arc<N>, &Arc<N>, &mut E) -> bool) {
+        self.path_graph.retain_edges(|mut graph, edge| {
+            let (from, to) = graph.edge_endpoints(edge).unwrap();
+            let (from, to) = (graph[from].clone(), graph[to].clone());
+            keep(&from, &to, &mut graph[edge])
+        });
+    }
+
+    /// Removes the nodes that can't be reached from `roots`, and returns their paths.
+    pub fn remove_unreachable(
+        &mut self,
+        roots: impl IntoIterator<Item = NodeIndex>,
+    ) -> Vec<Arc<N>> {
+        let mut dfs = Dfs::empty(&self.path_graph);
+        for root in roots {
+            dfs.move_to(root);
+            while dfs.next(&self.path_graph).is_some() {}
+        }
+        let unreachable = self
+            .path_graph
+            .node_indices()
+            .filter(|node| !dfs.discovered.is_visited(node))
+            .collect::<Vec<_>>();
+        unreachable
+            .into_iter()
+            .map(|node| {
+                let path = self.path_graph.remove_node(node).unwrap();
+                self.node_indices_by_path.remove(&path);
+                path
+            })
+            .collect()
+    }
+
+    /// Returns the graph of the groups of nodes, where `group_of` gives the group of each path,
+    /// or `None` to leave the path out. Each edge between two groups holds the edges between
+    /// their nodes, while the edges inside a group are dropped.
+    pub fn collapse<G: ?Sized + Hash + Ord>(
+        &self,
+        mut group_of: impl FnMut(&Arc<N>) -> Option<Arc<G>>,
+    ) -> DependencyGraph<Vec<EdgeIndex>, G> {
+        let mut collapsed = DependencyGraph::<Vec<EdgeIndex>, G>::default();
+        let mut groups = HashMap::<NodeIndex, NodeIndex>::default();
+        for node in self.path_graph.node_indices() {
+            if let Some(group) = group_of(&self.path_graph[node]) {
+                groups.insert(node, collapsed.get_path_index_or_insert(&group).0);
+            }
+        }
+        for edge_ref in self.path_graph.edge_references() {
+            let (Some(&from), Some(&to)) = (
+                groups.get(&edge_ref.source()),
+                groups.get(&edge_ref.target()),
+            ) else {
+                continue;
+            };
+            if from == to {
+                continue;
+            }
+            match collapsed.path_graph.find_edge(from, to) {
+                Some(edge) => collapsed.path_graph[edge].push(edge_ref.id()),
+                None => collapsed.add_edge(from, to, vec![edge_ref.id()]),
+            }
+        }
+        collapsed
+    }
+
+    /// Returns the strongly connected components that contain a cycle, i.e. the ones with more
+    /// than one node or with a node importing itself, with the largest ones first.
+    pub fn find_cyclic_components(&self) -> Vec<Vec<NodeIndex>> {
+        let mut components = kosaraju_scc(&self.path_graph)
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1 || self.path_graph.contains_edge(component[0], component[0])
+            })
+            .map(|mut component| {
+                component.sort_unstable_by_key(|node| &self.path_graph[*node]);
+                component
+            })
+            .collect::<Vec<_>>();
+        components.sort_unstable_by(|a, b| {
+            b.len()
+                .cmp(&a.len())
+                .then_with(|| self.path_graph[a[0]].cmp(&self.path_graph[b[0]]))
+        });
+        components
+    }
+
+    /// Returns a finder for the shortest cycles going through the edges of the graph.
+    pub fn shortest_cycles(&self) -> ShortestCycles<'_, E, N> {
+        let mut components = vec![usize::MAX; self.path_graph.node_bound()];
+        for (component, nodes) in kosaraju_scc(&self.path_graph).into_iter().enumerate() {
+            for node in nodes {
+                components[node.index()] = component;
+            }
+        }
+        ShortestCycles {
+            path_graph: &self.path_graph,
+            components,
+            space: TraversalSpace::new(&self.path_graph),
+        }
+    }
+
+    /// Returns the simple cycles in the graph within `limits`, shortest first, each as the
+    /// sequence of edges `(from, to, edge)` that forms it. The last edge of a cycle leads back to
+    /// the first node. Without limits, all the cycles are listed with Johnson's algorithm.
+    ///
+    /// Only the cycles for which `keep` returns true given their edges are listed, and the limits
+    /// apply to these cycles.
+    pub fn find_cycles(
+        &self,
+        limits: &CycleLimits,
+        mut keep: impl FnMut(&[&E]) -> bool,
+    ) -> (
+        impl Iterator<Item = impl Iterator<Item = (&Arc<N>, &Arc<N>, &E)>>,
+        Completeness,
+    ) {
+        let mut keep_nodes = |cycle: &[NodeIndex]| {
+            let edges = cycle
+                .iter()
+                .zip(cycle.iter().cycle().skip(1))
+                .map(|(from, to)| {
+                    let edge = self.path_graph.find_edge(*from, *to).unwrap();
+                    &self.path_graph[edge]
+                })
+                .collect::<Vec<_>>();
+            keep(&edges)
+        };
+        let BoundedCycles {
+            cycles,
+            completeness,
+        } = if limits.is_unlimited() {
+            // Johnson's algorithm lists all the cycles in a single pass, where the bounded search
+            // would go through the graph again for each length
+            let mut cycles = find_simple_cycles(&self.path_graph)
+                .filter(|cycle| keep_nodes(cycle))
+                .collect::<Vec<_>>();
+            cycles.sort_by_key(Vec::len);
+            BoundedCycles {
+                cycles,
+                completeness: Completeness::Complete,
+            }
+        } else {
+            find_bounded_simple_cycles(&self.path_graph, limits, keep_nodes)
+        };
+        let cycles = cycles.into_iter().map(move |cycle| {
+            let mut targets = cycle.clone();
+            targets.rotate_left(1);
+            cycle.into_iter().zip(targets).map(move |(from, to)| {
+                let edge = self.path_graph.find_edge(from, to).unwrap();
+                (
+                    &self.path_graph[from],
+                    &self.path_graph[to],
+                    &self.path_graph[edge],
+                )
+            })
+        });
+        (cycles, completeness)
+    }
+}
+
+/// Finds the shortest cycles going through edges of a [`DependencyGraph`]. A cycle stays within
+/// the strongly connected component of its edges, so each search only visits that component.
+pub struct ShortestCycles<'a, E, N: ?Sized> {
+    path_graph: &'a StableDiGraph<Arc<N>, E>,
+    /// The strongly connected component of each node, by node index.
+    components: Vec<usize>,
+    space: TraversalSpace<&'a StableDiGraph<Arc<N>, E>>,
+}
+
+impl<'a, E, N: ?Sized + Ord> ShortestCycles<'a, E, N> {
+    /// Returns the files of one of the shortest cycles going through the edge from `from` to
+    /// `to`, starting with `from`, or `None` if the edge isn't part of a cycle. Ties go to the
+    /// cycle whose files come first.
+    pub fn find(&mut self, from: NodeIndex, to: NodeIndex) -> Option<Vec<&'a Arc<N>>> {
+        let component = self.components[from.index()];
+        if self.components[to.index()] != component {
+            return None;
+        }
+        let components = &self.components;
+        let path_graph = self.path_graph;
+        let backtrack_edges = self.space.find_shortest_backtrack_edges(to, from, |node| {
+            components[node.index()] == component
+        })?;
+        let mut cycle = backtrack_edges
+            .map(|edge| {
+                let (edge_from, _) = path_graph.edge_endpoints(edge).unwrap();
+                &path_graph[edge_from]
+            })
+            .collect::<Vec<_>>();
+        cycle.push(&path_graph[from]);
+        cycle.reverse();
+        Some(cycle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ops::Deref;
+
+    #[test]
+    fn test_find_cycles() {
+        let mut graph = DependencyGraph::<&'static str>::default();
+        let [a, b, c] = ["a", "b", "c"].map(|path| {
+            graph
+                .get_path_index_or_insert(&Arc::from(Path::new(path)))
+                .0
+        });
+        graph.add_edge(a, b, "a-b");
+        graph.add_edge(b, a, "b-a");
+        graph.add_edge(b, c, "b-c");
+        graph.add_edge(c, c, "c-c");
+
+        let (cycles, completeness) = graph.find_cycles(&CycleLimits::default(), |_| true);
+        assert_eq!(completeness, Completeness::Complete);
+        let mut cycles = cycles
+            .map(|cycle| {
+                cycle
+                    .map(|(from, to, edge)| (from.deref(), to.deref(), *edge))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        cycles.iter_mut().for_each(|cycle| cycle.sort_unstable());
+        cycles.sort_unstable();
+        assert_eq!(
+            cycles,
+            vec![
+                vec![
+                    (Path::new("a"), Path::new("b"), "a-b"),
+                    (Path::new("b"), Path::new("a"), "b-a")
+                ],
+                vec![(Path::new("c"), Path::new("c"), "c-c")],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_shortest_cycle() {
+        let mut graph = DependencyGraph::<()>::default();
+        let [a, b, c, d] = ["a", "b", "c", "d"].map(|path| {
+            graph
+                .get_path_index_or_insert(&Arc::from(Path::new(path)))
+                .0
+        });
+        for (from, to) in [(a, b), (b, c), (c, d), (d, a), (c, a)] {
+            graph.add_edge(from, to, ());
+        }
+        let mut cycles = graph.shortest_cycles();
+        let cycle = cycles.find(a, b).unwrap();
+        assert_eq!(
+            cycle
+                .iter()
+                .map(|path| path.as_ref())
+                .collect::<Vec<&Path>>(),
+            ["a", "b", "c"].map(Path::new)
+        );
+        let cycle = cycles.find(c, d).unwrap();
+        assert_eq!(
+            cycle
+                .iter()
+                .map(|path| path.as_ref())
+                .collect::<Vec<&Path>>(),
+            ["c", "d", "a", "b"].map(Path::new)
+        );
+
+        let e = graph.get_path_index_or_insert(&Arc::from(Path::new("e"))).0;
+        graph.add_edge(a, e, ());
+        assert!(graph.shortest_cycles().find(a, e).is_none());
+    }
+
+    #[test]
+    fn test_find_shortest_cycle_ties() {
+        let mut graph = DependencyGraph::<()>::default();
+        let [a, b, d, c] = ["a", "b", "d", "c"].map(|path| {
+            graph
+                .get_path_index_or_insert(&Arc::from(Path::new(path)))
+                .0
+        });
+        for (from, to) in [(a, b), (b, c), (c, a), (b, d), (d, a)] {
+            graph.add_edge(from, to, ());
+        }
+        let cycle = graph.shortest_cycles().find(a, b).unwrap();
+        assert_eq!(
+            cycle
+                .iter()
+                .map(|path| path.as_ref())
+                .collect::<Vec<&Path>>(),
+            ["a", "b", "c"].map(Path::new)
+        );
+    }
+
+    #[test]
+    fn test_collapse() {
+        let mut graph = DependencyGraph::<()>::default();
+        let [a1, a2, b1, c1] = ["a/1", "a/2", "b/1", "c/1"].map(|path| {
+            graph
+                .get_path_index_or_insert(&Arc::from(Path::new(path)))
+                .0
+        });
+        for (from, to) in [(a1, a2), (a1, b1), (a2, b1), (b1, a2), (b1, c1)] {
+            graph.add_edge(from, to, ());
+        }
+        let collapsed = graph.collapse(|path| {
+            let group = path.parent().unwrap().to_str().unwrap();
+            (group != "c").then(|| Arc::from(group))
+        });
+        collapsed.assert_consistency();
+        let mut edges = collapsed
+            .edges()
+            .map(|(from, to, edges)| (from, to, edges.len()))
+            .collect::<Vec<_>>();
+        edges.sort_unstable();
+        assert_eq!(edges, vec![("a", "b", 2), ("b", "a", 1)]);
+    }
+
+    #[test]
+    fn test_remove_unreachable() {
+        let mut graph = DependencyGraph::<()>::default();
+        let [a, b, c, d, e] = ["a", "b", "c", "d", "e"].map(|path| {
+            graph
+                .get_path_index_or_insert(&Arc::from(Path::new(path)))
+                .0
+        });
+        for (from, to) in [(a, b), (c, d), (d, c), (e, a)] {
+            graph.add_edge(from, to, ());
+        }
+        let mut removed = graph.remove_unreachable([a, d]);
+        removed.sort_unstable();
+        assert_eq!(removed, [Arc::from(Path::new("e"))]);
+        graph.assert_consistency();
+        assert_eq!(graph.edges().count(), 3);
+    }
+
+    #[test]
+    fn test_find_cyclic_components() {
+        let mut graph = DependencyGraph::<()>::default();
+        let [a, b, c, d, e, f] = ["a", "b", "c", "d", "e", "f"].map(|path| {
+            graph
+                .get_path_index_or_insert(&Arc::from(Path::new(path)))
+                .0
+        });
+        for (from, to) in [(f, e), (e, f), (a, b), (b, c), (c, a), (c, d), (d, d)] {
+            graph.add_edge(from, to, ());
+        }
+        assert_eq!(
+            graph.find_cyclic_components(),
+            vec![vec![a, b, c], vec![e, f], vec![d]]
+        );
     }
 }
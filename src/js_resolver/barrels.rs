@@ -0,0 +1,152 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use smallvec::SmallVec;
+
+use super::{parse_imports::Exports, ImportedNames, JsImport};
+use crate::{
+    dep_graph::DependencyGraph,
+    hash::{HashMap, HashSet},
+};
+
+/// The exports of a file, with the re-exported files resolved, used to trace imports through
+/// barrel files.
+#[derive(Debug, Default)]
+pub struct FileExports {
+    local: HashSet<String>,
+    /// The file and the name there of each re-exported name, `None` for a namespace.
+    reexported: HashMap<String, (PathBuf, Option<String>)>,
+    star_reexported: Vec<PathBuf>,
+}
+
+impl FileExports {
+    /// Returns the exports of a file, where `reexported_paths` gives the resolved path of the
+    /// re-export specifiers by index. The re-exports of the other specifiers are left out.
+    pub(super) fn new(exports: Exports, reexported_paths: &HashMap<usize, PathBuf>) -> Self {
+        Self {
+            local: exports.local.into_iter().map(str::to_owned).collect(),
+            reexported: exports
+                .reexported
+                .into_iter()
+                .filter_map(|(exported, index, imported)| {
+                    let path = reexported_paths.get(&index)?.clone();
+                    Some((exported.to_owned(), (path, imported.map(str::to_owned))))
+                })
+                .collect(),
+            star_reexported: exports
+                .star_reexported
+                .into_iter()
+                .filter_map(|index| reexported_paths.get(&index).cloned())
+                .collect(),
+        }
+    }
+
+    fn is_barrel(&self) -> bool {
+        !self.reexported.is_empty() || !self.star_reexported.is_empty()
+    }
+}
+
+/// Returns the file that defines the export `name` of the file at `path`, following its
+/// re-exports, or `None` if it has no such export.
+fn find_provider<'a>(
+    exports_by_path: &'a HashMap<PathBuf, FileExports>,
+    path: &'a Path,
+    name: &'a str,
+    visited: &mut HashSet<(&'a Path, &'a str)>,
+) -> Option<&'a Path> {
+    // Re-exports can form cycles, which don't provide anything
+    if !visited.insert((path, name)) {
+        return None;
+    }
+    let exports = exports_by_path.get(path)?;
+    if exports.local.contains(name) {
+        return Some(path);
+    }
+    if let Some((reexported_path, imported)) = exports.reexported.get(name) {
+        return Some(match imported {
+            Some(imported) => find_provider(exports_by_path, reexported_path, imported, visited)
+                .unwrap_or(reexported_path),
+            None => reexported_path,
+        });
+    }
+    // `export *` leaves out the default export
+    if name == "default" {
+        return None;
+    }
+    exports
+        .star_reexported
+        .iter()
+        .find_map(|star_path| find_provider(exports_by_path, star_path, name, visited))
+}
+
+/// Moves the imports of names re-exported by barrel files, such as an `index.ts` made of
+/// `export * from './x'`, to the edges towards the files that define them. The imports of the
+/// whole file, and of names that aren't found, stay on the edge to the barrel. Imports of names
+/// that the importing file defines itself are dropped.
+///
+/// `exports_by_path` holds the exports of the files by absolute path, and the paths of `graph`
+/// are relative to `base_path`.
+pub fn trace_barrel_imports(
+    graph: &mut DependencyGraph<SmallVec<[JsImport; 1]>>,
+    base_path: &Path,
+    exports_by_path: &HashMap<PathBuf, FileExports>,
+) {
+    let mut moved = Vec::<(Arc<Path>, Arc<Path>, JsImport)>::new();
+    graph.retain_edges(|source, target, imports| {
+        let target_path = base_path.join(target);
+        if !exports_by_path
+            .get(&target_path)
+            .is_some_and(FileExports::is_barrel)
+        {
+            return true;
+        }
+        imports.retain_mut(|import| {
+            let ImportedNames::Named(names) = &import.names else {
+                return true;
+            };
+            let mut names_by_provider = HashMap::<&Path, Vec<String>>::default();
+            for name in names {
+                let provider =
+                    find_provider(exports_by_path, &target_path, name, &mut HashSet::default())
+                        .unwrap_or(&target_path);
+                names_by_provider
+                    .entry(provider)
+                    .or_default()
+                    .push(name.clone());
+            }
+            let mut target_names = None;
+            for (provider, names) in names_by_provider {
+                let provider =
+                    Arc::<Path>::from(pathdiff::diff_paths(provider, base_path).unwrap());
+                if provider == *target {
+                    target_names = Some(names);
+                } else if provider != *source {
+                    moved.push((
+                        source.clone(),
+                        provider,
+                        JsImport {
+                            names: ImportedNames::Named(names),
+                            ..import.clone()
+                        },
+                    ));
+                }
+            }
+            let Some(target_names) = target_names else {
+                return false;
+            };
+            import.names = ImportedNames::Named(target_names);
+            true
+        });
+        !imports.is_empty()
+    });
+
+    for (source, provider, import) in moved {
+        let (from, _) = graph.get_path_index_or_insert(&source);
+        let (to, _) = graph.get_path_index_or_insert(&provider);
+        let imports = graph.edge_mut_or_insert_default(from, to);
+        imports.push(import);
+        imports.sort_by_key(|import| import.span.start);
+    }
+}
@@ -1,10 +1,11 @@
+mod barrels;
 mod parse_imports;
 use std::{
     cell::RefCell,
     ffi::OsStr,
     io,
     path::{Component, Path, PathBuf},
-    sync::Arc,
+    sync::Mutex,
 };
 
 use bumpalo::Bump;
@@ -15,8 +16,9 @@ use oxc_resolver::{FileSystem, ResolveOptions, ResolverGeneric};
 use oxc_span::{SourceType, Span};
 use smallvec::SmallVec;
 
-use crate::collect_deps::DiscoverDependency;
-use parse_imports::{parse_imports, Imports};
+use crate::{collect_deps::DiscoverDependency, hash};
+pub use barrels::{trace_barrel_imports, FileExports};
+use parse_imports::{parse_imports, Imports, Specifier};
 use thread_local::ThreadLocal;
 
 #[derive(Debug)]
@@ -29,8 +31,71 @@ pub enum JsDiscoverDependencyError {
     },
 }
 
+/// An import of a file by another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsImport {
+    /// The import specifier in the importing file.
+    pub span: Span,
+    /// The import statement or call in the importing file.
+    pub statement: Span,
+    pub kind: ImportKind,
+    pub names: ImportedNames,
+    /// Whether the importing file reads the imported bindings while it's evaluated, rather than
+    /// only in functions called later. Reading a binding of a file that is still being evaluated,
+    /// as happens in cycles, throws or yields `undefined`.
+    pub used_at_top_level: bool,
+    /// Set when the import is preceded by a `decycle-ignore-next-line` comment.
+    pub ignore: Option<IgnoreComment>,
+}
+
+/// The syntax of an import.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImportKind {
+    /// `import ... from './x'` or `import './x'`.
+    Static,
+    /// `export ... from './x'`.
+    ReExport,
+    /// `require('./x')`.
+    Require,
+    /// `import('./x')`, which loads the file lazily.
+    Dynamic,
+    /// TypeScript's `import x = require('./x')`.
+    ImportEquals,
+    /// `import type`, `export type ... from` or `import type x = require('./x')`, which are
+    /// erased when compiling. Only recorded when type imports are collected.
+    Type,
+}
+
+/// The exports that an import reads from the imported file, or re-exports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportedNames {
+    /// The whole file, as with namespace imports, `export *`, `require()` and imports for side
+    /// effects.
+    All,
+    /// Named exports, `default` included.
+    Named(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IgnoreComment {
+    /// The text following the directive, if any.
+    pub reason: Option<String>,
+}
+
+/// How non-relative specifiers, such as `@/components/Foo` or `some-package`, are handled.
+#[derive(Debug, Clone, Default)]
+pub enum BareSpecifiers {
+    /// Skip them, which leaves out path aliases and workspace packages along with third-party
+    /// packages.
+    #[default]
+    Skip,
+    /// Resolve them, leaving out the files inside a directory with one of these names, such as
+    /// `node_modules`. Imports of Node.js builtin modules are skipped.
+    Resolve { external_directories: Vec<String> },
+}
+
 pub struct ResetOnDrop<'a>(&'a mut Allocator);
-impl<'a> Drop for ResetOnDrop<'a> {
+impl Drop for ResetOnDrop<'_> {
     fn drop(&mut self) {
         self.0.reset()
     }
@@ -39,20 +104,80 @@ impl<'a> Drop for ResetOnDrop<'a> {
 pub struct JsDiscoverDependency<FS> {
     fs: FS,
     path_resolver: ResolverGeneric<FS>,
+    bare_specifiers: BareSpecifiers,
     allocator: ThreadLocal<RefCell<Allocator>>,
+    /// Whether the type-only imports are recorded too.
+    type_imports: bool,
+    /// The exports of the discovered files by path, when they're collected.
+    file_exports: Option<Mutex<hash::HashMap<PathBuf, FileExports>>>,
 }
 impl<FS: Clone + FileSystem> JsDiscoverDependency<FS> {
     pub fn new(fs: FS, resolve_options: ResolveOptions) -> Self {
         Self {
             fs: fs.clone(),
             path_resolver: ResolverGeneric::new_with_file_system(fs, resolve_options),
+            bare_specifiers: BareSpecifiers::Skip,
             allocator: ThreadLocal::new(),
+            type_imports: false,
+            file_exports: None,
         }
     }
+
+    /// Sets how non-relative specifiers are handled. Resolving them requires
+    /// [`ResolveOptions::builtin_modules`] to tell Node.js builtin modules apart.
+    pub fn with_bare_specifiers(mut self, bare_specifiers: BareSpecifiers) -> Self {
+        self.bare_specifiers = bare_specifiers;
+        self
+    }
+
+    /// Also records the type-only imports, as imports of kind [`ImportKind::Type`], and resolves
+    /// specifiers to `.d.ts` files after the other extensions.
+    pub fn with_type_imports(mut self) -> Self {
+        self.type_imports = true;
+        let mut resolve_options = self.path_resolver.options().clone();
+        if !resolve_options.extensions.iter().any(|ext| ext == ".d.ts") {
+            resolve_options.extensions.push(".d.ts".to_owned());
+            self.path_resolver = self.path_resolver.clone_with_options(resolve_options);
+        }
+        self
+    }
+
+    /// Collects the exports of the discovered files, for [`trace_barrel_imports`].
+    pub fn with_file_exports(mut self) -> Self {
+        self.file_exports = Some(Mutex::default());
+        self
+    }
+
+    /// Returns the exports of the discovered files by path, empty unless they're collected.
+    pub fn into_file_exports(self) -> hash::HashMap<PathBuf, FileExports> {
+        self.file_exports
+            .map(|file_exports| file_exports.into_inner().unwrap())
+            .unwrap_or_default()
+    }
+}
+
+impl<FS> JsDiscoverDependency<FS> {
+    /// Whether a bare specifier resolved to `resolved_path` should be left out.
+    fn is_external(&self, resolved_path: &Path) -> bool {
+        let BareSpecifiers::Resolve {
+            external_directories,
+        } = &self.bare_specifiers
+        else {
+            return false;
+        };
+        resolved_path.components().any(|component| {
+            let Component::Normal(name) = component else {
+                return false;
+            };
+            external_directories
+                .iter()
+                .any(|directory| name == OsStr::new(directory))
+        })
+    }
 }
 
 impl<FS: FileSystem> DiscoverDependency for JsDiscoverDependency<FS> {
-    type Edge = SmallVec<[Span; 1]>;
+    type Edge = SmallVec<[JsImport; 1]>;
 
     type Error = JsDiscoverDependencyError;
 
@@ -76,6 +201,7 @@ impl<FS: FileSystem> DiscoverDependency for JsDiscoverDependency<FS> {
             Imports {
                 specifiers,
                 non_literal_imports,
+                exports,
             },
             parse_errors,
         ) = parse_imports(
@@ -83,19 +209,34 @@ impl<FS: FileSystem> DiscoverDependency for JsDiscoverDependency<FS> {
             SourceType::from_path(file_path)
                 .unwrap_or_else(|_| SourceType::default().with_jsx(true).with_module(true)),
             &file_content,
+            self.type_imports,
         );
 
-        let mut spans_by_dep =
-            HashMap::<PathBuf, SmallVec<[Span; 1]>, DefaultHashBuilder, &Bump>::with_capacity_in(
-                specifiers.len(),
-                allocator,
-            );
-        for (specifier, span) in specifiers {
-            if !matches!(
+        let mut imports_by_dep = HashMap::<
+            PathBuf,
+            SmallVec<[JsImport; 1]>,
+            DefaultHashBuilder,
+            &Bump,
+        >::with_capacity_in(specifiers.len(), allocator);
+        let mut reexported_paths = hash::HashMap::<usize, PathBuf>::default();
+        for (
+            index,
+            Specifier {
+                value: specifier,
+                span,
+                statement,
+                kind,
+                names,
+                used_at_top_level,
+                ignore,
+            },
+        ) in specifiers.into_iter().enumerate()
+        {
+            let is_bare = !matches!(
                 Path::new(specifier).components().next(),
                 Some(Component::CurDir | Component::ParentDir)
-            ) {
-                // skip non-relative specifiers
+            );
+            if is_bare && matches!(self.bare_specifiers, BareSpecifiers::Skip) {
                 continue;
             }
             let resolution = match self
@@ -103,19 +244,45 @@ impl<FS: FileSystem> DiscoverDependency for JsDiscoverDependency<FS> {
                 .resolve(file_path.parent().unwrap_or(file_path), specifier)
             {
                 Ok(ok) => ok,
+                Err(oxc_resolver::ResolveError::Builtin(_)) => continue,
                 Err(err) => {
                     resolve_errors.push((err, span));
                     continue;
                 }
             };
             let resolved_path = resolution.into_path_buf();
+            if is_bare && self.is_external(&resolved_path) {
+                continue;
+            }
             if !matches!(
                 resolved_path.extension().and_then(OsStr::to_str),
                 Some("js" | "ts" | "jsx" | "tsx")
             ) {
                 continue;
             }
-            spans_by_dep.entry(resolved_path).or_default().push(span);
+            // Imports may be re-exported too, as in `import a from './a'; export { a }`
+            if self.file_exports.is_some() {
+                reexported_paths.insert(index, resolved_path.clone());
+            }
+            imports_by_dep
+                .entry(resolved_path)
+                .or_default()
+                .push(JsImport {
+                    span,
+                    statement,
+                    kind,
+                    names: names.map_or(ImportedNames::All, |names| {
+                        ImportedNames::Named(names.into_iter().map(str::to_owned).collect())
+                    }),
+                    used_at_top_level,
+                    ignore,
+                });
+        }
+        if let Some(file_exports) = &self.file_exports {
+            file_exports.lock().unwrap().insert(
+                file_path.to_path_buf(),
+                FileExports::new(exports, &reexported_paths),
+            );
         }
 
         let error = if parse_errors.is_empty()
@@ -131,6 +298,6 @@ impl<FS: FileSystem> DiscoverDependency for JsDiscoverDependency<FS> {
             })
         };
 
-        (spans_by_dep.into_iter().collect(), error)
+        (imports_by_dep.into_iter().collect(), error)
     }
 }
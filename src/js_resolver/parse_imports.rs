@@ -1,30 +1,133 @@
+use std::ops::Range;
+
 use oxc_allocator::Allocator;
 use oxc_ast::{
-    ast::{Argument, Expression, ImportOrExportKind},
+    ast::{
+        Argument, BindingPatternKind, Declaration, ExportDefaultDeclaration,
+        ExportDefaultDeclarationKind, Expression, IdentifierReference, ImportDeclarationSpecifier,
+        NewExpression, PropertyDefinition, StringLiteral, TSInterfaceDeclaration, TSType,
+        TSTypeAliasDeclaration, VariableDeclarator,
+    },
+    syntax_directed_operations::BoundNames,
     visit::{
         walk::{
-            walk_call_expression, walk_export_all_declaration, walk_export_named_declaration,
-            walk_import_declaration, walk_import_expression, walk_program,
-            walk_ts_import_equals_declaration,
+            walk_call_expression, walk_export_all_declaration, walk_export_default_declaration,
+            walk_export_named_declaration, walk_import_declaration, walk_import_expression,
+            walk_new_expression, walk_program, walk_property_definition,
+            walk_ts_import_equals_declaration, walk_variable_declarator,
         },
         Visit,
     },
+    AstKind, Trivias,
 };
 use oxc_diagnostics::OxcDiagnostic;
 use oxc_parser::Parser;
 use oxc_span::GetSpan;
 use oxc_span::{SourceType, Span};
 
+use super::{IgnoreComment, ImportKind};
+use crate::hash::{HashMap, HashSet};
+
+/// Comment that leaves the imports of the next line out of the dependency graph, optionally
+/// followed by the reason.
+pub const IGNORE_DIRECTIVE: &str = "decycle-ignore-next-line";
+
 #[derive(Default)]
 pub struct Imports<'a> {
-    pub specifiers: Vec<(&'a str, Span)>,
+    pub specifiers: Vec<Specifier<'a>>,
     pub non_literal_imports: Vec<Span>,
+    pub exports: Exports<'a>,
+}
+
+/// The names that a file exports.
+#[derive(Default)]
+pub struct Exports<'a> {
+    /// Names of the bindings exported from the scope of the file, `default` included.
+    pub local: Vec<&'a str>,
+    /// Names re-exported with `export { ... } from`, or imported and then exported, along with
+    /// the index of the specifier and the name in the imported file, or `None` for a namespace
+    /// such as `export * as name from`.
+    pub reexported: Vec<(&'a str, usize, Option<&'a str>)>,
+    /// Indices of the specifiers of `export * from`.
+    pub star_reexported: Vec<usize>,
+}
+
+pub struct Specifier<'a> {
+    pub value: &'a str,
+    pub span: Span,
+    /// The import statement or call.
+    pub statement: Span,
+    pub kind: ImportKind,
+    /// The exports that the import reads or re-exports, or `None` for the whole file.
+    pub names: Option<Vec<&'a str>>,
+    /// Whether the file reads an imported binding, or calls `require`, while it's evaluated
+    /// rather than only in functions that aren't called then.
+    pub used_at_top_level: bool,
+    /// Set when the import statement or call starts on the line after an ignore comment.
+    pub ignore: Option<IgnoreComment>,
+}
+
+/// Returns the range of the line following each ignore comment, along with the comment.
+fn ignored_lines(source: &str, trivias: &Trivias) -> Vec<(Range<u32>, IgnoreComment)> {
+    trivias
+        .comments()
+        .filter_map(|(_, span)| {
+            let rest = span
+                .source_text(source)
+                .trim_start()
+                .strip_prefix(IGNORE_DIRECTIVE)?;
+            if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+                return None;
+            }
+            let reason = rest.trim();
+            let end = span.end as usize;
+            let next_line_start = end + source[end..].find('\n')? + 1;
+            let next_line_end = source[next_line_start..]
+                .find('\n')
+                .map_or(source.len(), |index| next_line_start + index);
+            Some((
+                next_line_start as u32..next_line_end as u32,
+                IgnoreComment {
+                    reason: (!reason.is_empty()).then(|| reason.to_owned()),
+                },
+            ))
+        })
+        .collect()
+}
+
+/// A function that may be called, by its span or by the name it's bound to.
+enum Callee<'a> {
+    Function(Span),
+    Name(&'a str),
+}
+
+impl<'a> Callee<'a> {
+    /// Returns the function that `expr` calls or passes as a callback, if it's one.
+    fn of_expression(expr: &Expression<'a>) -> Option<Self> {
+        match expr.without_parenthesized() {
+            Expression::FunctionExpression(func) => Some(Callee::Function(func.span)),
+            Expression::ArrowFunctionExpression(arrow) => Some(Callee::Function(arrow.span)),
+            Expression::Identifier(ident) => Some(Callee::Name(ident.name.as_str())),
+            _ => None,
+        }
+    }
+
+    fn of_argument(arg: &Argument<'a>) -> Option<Self> {
+        match arg {
+            Argument::FunctionExpression(func) => Some(Callee::Function(func.span)),
+            Argument::ArrowFunctionExpression(arrow) => Some(Callee::Function(arrow.span)),
+            Argument::Identifier(ident) => Some(Callee::Name(ident.name.as_str())),
+            _ => None,
+        }
+    }
 }
 
+/// Returns the imports of `source`, including the type-only ones with `type_imports`.
 pub fn parse_imports<'a>(
     allocator: &'a Allocator,
     source_type: SourceType,
     source: &'a str,
+    type_imports: bool,
 ) -> (Imports<'a>, Vec<OxcDiagnostic>) {
     let parser = Parser::new(allocator, source, source_type);
     let parse_return = parser.parse();
@@ -34,14 +137,211 @@ pub fn parse_imports<'a>(
 
     #[derive(Default)]
     struct ImportsVisitor<'a> {
-        specifiers: Vec<(&'a str, Span)>,
+        type_imports: bool,
+        specifiers: Vec<Specifier<'a>>,
         non_literal_imports: Vec<Span>,
+        exports: Exports<'a>,
+        /// Local names of the imported bindings, with the index of their specifier.
+        bindings: Vec<(&'a str, usize)>,
+        /// The index of the specifier and the imported name of each imported binding by local
+        /// name, `None` for a namespace.
+        imported_bindings: HashMap<&'a str, (usize, Option<&'a str>)>,
+        /// `(exported, local)` names of the bindings exported from the scope of the file.
+        exported_bindings: Vec<(&'a str, &'a str)>,
+        /// The scopes of the functions around the node being visited, the innermost last. The top
+        /// level is scope 0, and each function gets the next one.
+        scopes: Vec<usize>,
+        scope_count: usize,
+        scopes_by_function: HashMap<Span, usize>,
+        /// Functions by the name of the function declarations and variables they're bound to.
+        functions_by_name: HashMap<&'a str, Vec<Span>>,
+        /// The functions called, or passed as callbacks, in each scope.
+        callees: HashMap<usize, Vec<Callee<'a>>>,
+        /// Names read in each scope.
+        references: Vec<(usize, &'a str)>,
+        /// Indices of the `require` specifiers, with the scope they're called in.
+        requires: Vec<(usize, usize)>,
+    }
+    impl<'a> ImportsVisitor<'a> {
+        fn push(&mut self, source: &StringLiteral<'a>, kind: ImportKind, statement: Span) {
+            let specifier = Specifier {
+                value: source.value.as_str(),
+                span: source.span,
+                statement,
+                kind,
+                names: None,
+                used_at_top_level: false,
+                ignore: None,
+            };
+            if kind == ImportKind::Require {
+                self.requires.push((self.specifiers.len(), self.scope()));
+            }
+            self.specifiers.push(specifier);
+        }
+
+        fn scope(&self) -> usize {
+            self.scopes.last().copied().unwrap_or(0)
+        }
+
+        /// Enters a new scope, for the function at `span` if there is one.
+        fn push_scope(&mut self, span: Option<Span>) {
+            self.scope_count += 1;
+            self.scopes.push(self.scope_count);
+            if let Some(span) = span {
+                self.scopes_by_function.insert(span, self.scope_count);
+            }
+        }
+
+        fn push_callee(&mut self, callee: Option<Callee<'a>>) {
+            if let Some(callee) = callee {
+                self.callees.entry(self.scope()).or_default().push(callee);
+            }
+        }
+
+        /// Returns the scopes whose code runs while the file is evaluated: the top level, and
+        /// the functions it calls or passes as callbacks, recursively. Functions called by name
+        /// are matched by name only, regardless of scoping.
+        fn running_scopes(&self) -> HashSet<usize> {
+            let mut running = HashSet::from_iter([0]);
+            let mut stack = vec![0];
+            while let Some(scope) = stack.pop() {
+                for callee in self.callees.get(&scope).into_iter().flatten() {
+                    let spans = match callee {
+                        Callee::Function(span) => std::slice::from_ref(span),
+                        Callee::Name(name) => self
+                            .functions_by_name
+                            .get(name)
+                            .map_or(&[][..], Vec::as_slice),
+                    };
+                    for span in spans {
+                        if let Some(&callee_scope) = self.scopes_by_function.get(span) {
+                            if running.insert(callee_scope) {
+                                stack.push(callee_scope);
+                            }
+                        }
+                    }
+                }
+            }
+            running
+        }
+
+        fn bind(&mut self, name: &'a str) {
+            self.bindings.push((name, self.specifiers.len() - 1));
+        }
+
+        /// Records that the last specifier binds `imported`, or the namespace for `None`, to the
+        /// local name `local`.
+        fn bind_import(&mut self, local: &'a str, imported: Option<&'a str>) {
+            let index = self.specifiers.len() - 1;
+            self.imported_bindings.insert(local, (index, imported));
+        }
+
+        /// Turns the exports of imported bindings, such as `import a from './a'; export { a }`,
+        /// into re-exports of the imported names.
+        fn reexport_imported_bindings(&mut self) {
+            for (exported, local) in &self.exported_bindings {
+                let Some(&(index, imported)) = self.imported_bindings.get(local) else {
+                    continue;
+                };
+                self.exports.local.retain(|name| name != exported);
+                self.exports.reexported.push((exported, index, imported));
+            }
+        }
+
+        /// Records the `(exported, local)` names re-exported by the last specifier.
+        fn reexport(&mut self, names: Vec<(&'a str, &'a str)>) {
+            let index = self.specifiers.len() - 1;
+            for (exported, local) in &names {
+                self.exports.reexported.push((exported, index, Some(local)));
+            }
+            self.set_names(Some(names.into_iter().map(|(_, local)| local).collect()));
+        }
+
+        /// Sets the names read by the last specifier, if there are any.
+        fn set_names(&mut self, names: Option<Vec<&'a str>>) {
+            self.specifiers.last_mut().unwrap().names = names.filter(|names| !names.is_empty());
+        }
     }
     impl<'a> Visit<'a> for ImportsVisitor<'a> {
+        // Parameter defaults and bodies of functions, methods and arrows run when called
+        fn enter_node(&mut self, kind: AstKind<'a>) {
+            match kind {
+                AstKind::Function(func) => {
+                    if let Some(id) = &func.id {
+                        self.functions_by_name
+                            .entry(id.name.as_str())
+                            .or_default()
+                            .push(func.span);
+                    }
+                    self.push_scope(Some(func.span));
+                }
+                AstKind::ArrowFunctionExpression(arrow) => self.push_scope(Some(arrow.span)),
+                _ => {}
+            }
+        }
+        fn leave_node(&mut self, kind: AstKind<'a>) {
+            if matches!(
+                kind,
+                AstKind::Function(_) | AstKind::ArrowFunctionExpression(_)
+            ) {
+                self.scopes.pop();
+            }
+        }
+        fn visit_property_definition(&mut self, def: &PropertyDefinition<'a>) {
+            // Instance properties are initialized by the constructor, which isn't followed
+            if def.r#static {
+                walk_property_definition(self, def);
+            } else {
+                self.push_scope(None);
+                walk_property_definition(self, def);
+                self.scopes.pop();
+            }
+        }
+        fn visit_variable_declarator(&mut self, declarator: &VariableDeclarator<'a>) {
+            if let (BindingPatternKind::BindingIdentifier(id), Some(init)) =
+                (&declarator.id.kind, &declarator.init)
+            {
+                if let Some(Callee::Function(span)) = Callee::of_expression(init) {
+                    self.functions_by_name
+                        .entry(id.name.as_str())
+                        .or_default()
+                        .push(span);
+                }
+            }
+            walk_variable_declarator(self, declarator);
+        }
+        fn visit_new_expression(&mut self, expr: &NewExpression<'a>) {
+            // Callbacks may be called right away, like the executor of a `Promise`
+            for arg in &expr.arguments {
+                self.push_callee(Callee::of_argument(arg));
+            }
+            walk_new_expression(self, expr);
+        }
+        fn visit_identifier_reference(&mut self, ident: &IdentifierReference<'a>) {
+            self.references.push((self.scope(), ident.name.as_str()));
+        }
+        // Types are erased, so the names they refer to are never read
+        fn visit_ts_type(&mut self, _ty: &TSType<'a>) {}
+        fn visit_ts_interface_declaration(&mut self, _decl: &TSInterfaceDeclaration<'a>) {}
+        fn visit_ts_type_alias_declaration(&mut self, _decl: &TSTypeAliasDeclaration<'a>) {}
+
         fn visit_export_all_declaration(&mut self, decl: &oxc_ast::ast::ExportAllDeclaration<'a>) {
-            if !decl.export_kind.is_type() {
-                self.specifiers
-                    .push((decl.source.value.as_str(), decl.source.span));
+            let kind = if decl.export_kind.is_type() {
+                ImportKind::Type
+            } else {
+                ImportKind::ReExport
+            };
+            if kind == ImportKind::ReExport || self.type_imports {
+                self.push(&decl.source, kind, decl.span);
+                let index = self.specifiers.len() - 1;
+                match &decl.exported {
+                    Some(exported) => {
+                        self.exports
+                            .reexported
+                            .push((exported.name().as_str(), index, None));
+                    }
+                    None => self.exports.star_reexported.push(index),
+                }
             }
             walk_export_all_declaration(self, decl);
         }
@@ -49,17 +349,145 @@ pub fn parse_imports<'a>(
             &mut self,
             decl: &oxc_ast::ast::ExportNamedDeclaration<'a>,
         ) {
-            if !decl.export_kind.is_type() {
-                if let Some(source) = &decl.source {
-                    self.specifiers.push((source.value.as_str(), source.span))
+            if let Some(source) = &decl.source {
+                let only_types = !decl.specifiers.is_empty()
+                    && decl
+                        .specifiers
+                        .iter()
+                        .all(|specifier| specifier.export_kind.is_type());
+                let kind = if decl.export_kind.is_type() || only_types {
+                    ImportKind::Type
+                } else {
+                    ImportKind::ReExport
+                };
+                if kind == ImportKind::ReExport || self.type_imports {
+                    self.push(source, kind, decl.span);
+                    let mut names = vec![];
+                    let mut type_names = vec![];
+                    for specifier in &decl.specifiers {
+                        let exported = specifier.exported.name().as_str();
+                        let local = specifier.local.name().as_str();
+                        if kind == ImportKind::ReExport && specifier.export_kind.is_type() {
+                            type_names.push((exported, local));
+                        } else {
+                            names.push((exported, local));
+                        }
+                    }
+                    self.reexport(names);
+                    if self.type_imports && !type_names.is_empty() {
+                        self.push(source, ImportKind::Type, decl.span);
+                        self.reexport(type_names);
+                    }
+                }
+            } else {
+                // Types are exported as well, since they can be imported without `type`
+                for specifier in &decl.specifiers {
+                    let exported = specifier.exported.name().as_str();
+                    self.exports.local.push(exported);
+                    self.exported_bindings
+                        .push((exported, specifier.local.name().as_str()));
+                }
+                decl.bound_names(&mut |ident| self.exports.local.push(ident.name.as_str()));
+                match &decl.declaration {
+                    Some(Declaration::TSTypeAliasDeclaration(decl)) => {
+                        self.exports.local.push(decl.id.name.as_str())
+                    }
+                    Some(Declaration::TSInterfaceDeclaration(decl)) => {
+                        self.exports.local.push(decl.id.name.as_str())
+                    }
+                    Some(Declaration::TSEnumDeclaration(decl)) => {
+                        self.exports.local.push(decl.id.name.as_str())
+                    }
+                    Some(Declaration::TSModuleDeclaration(decl)) => {
+                        self.exports.local.push(decl.id.name().as_str())
+                    }
+                    _ => {}
                 }
             }
             walk_export_named_declaration(self, decl);
         }
+        fn visit_export_default_declaration(&mut self, decl: &ExportDefaultDeclaration<'a>) {
+            self.exports.local.push("default");
+            if let ExportDefaultDeclarationKind::Identifier(ident) = &decl.declaration {
+                self.exported_bindings
+                    .push(("default", ident.name.as_str()));
+            }
+            walk_export_default_declaration(self, decl);
+        }
         fn visit_import_declaration(&mut self, decl: &oxc_ast::ast::ImportDeclaration<'a>) {
-            if !decl.import_kind.is_type() {
-                self.specifiers
-                    .push((decl.source.value.as_str(), decl.source.span))
+            // Imports whose specifiers all have `type` are erased like `import type`
+            let only_types = decl.specifiers.as_ref().is_some_and(|specifiers| {
+                !specifiers.is_empty()
+                    && specifiers.iter().all(|specifier| {
+                        matches!(
+                            specifier,
+                            ImportDeclarationSpecifier::ImportSpecifier(specifier)
+                                if specifier.import_kind.is_type()
+                        )
+                    })
+            });
+            let kind = if decl.import_kind.is_type() || only_types {
+                ImportKind::Type
+            } else {
+                ImportKind::Static
+            };
+            if kind == ImportKind::Static || self.type_imports {
+                self.push(&decl.source, kind, decl.span);
+                // Imports for side effects only read the whole file
+                let mut names = decl.specifiers.as_ref().map(|_| vec![]);
+                let mut type_names = vec![];
+                for specifier in decl.specifiers.iter().flatten() {
+                    // The bindings of type imports can only be used in types, which aren't read
+                    match specifier {
+                        ImportDeclarationSpecifier::ImportSpecifier(specifier) => {
+                            let local = specifier.local.name.as_str();
+                            let imported = specifier.imported.name().as_str();
+                            if kind == ImportKind::Static && specifier.import_kind.is_type() {
+                                type_names.push((local, imported));
+                                continue;
+                            }
+                            if kind == ImportKind::Static {
+                                self.bind(local);
+                            }
+                            self.bind_import(local, Some(imported));
+                            if let Some(names) = &mut names {
+                                names.push(imported);
+                            }
+                        }
+                        ImportDeclarationSpecifier::ImportDefaultSpecifier(specifier) => {
+                            let local = specifier.local.name.as_str();
+                            if kind == ImportKind::Static {
+                                self.bind(local);
+                            }
+                            self.bind_import(local, Some("default"));
+                            if let Some(names) = &mut names {
+                                names.push("default");
+                            }
+                        }
+                        ImportDeclarationSpecifier::ImportNamespaceSpecifier(specifier) => {
+                            let local = specifier.local.name.as_str();
+                            if kind == ImportKind::Static {
+                                self.bind(local);
+                            }
+                            self.bind_import(local, None);
+                            names = None;
+                        }
+                    }
+                }
+                self.set_names(names);
+                // The specifiers with `type` of a runtime import make a separate type import
+                if self.type_imports && !type_names.is_empty() {
+                    self.push(&decl.source, ImportKind::Type, decl.span);
+                    for (local, imported) in &type_names {
+                        self.bind_import(local, Some(imported));
+                    }
+                    self.set_names(Some(
+                        type_names
+                            .into_iter()
+                            .map(|(_, imported)| imported)
+                            .collect(),
+                    ));
+                }
             };
             walk_import_declaration(self, decl)
         }
@@ -67,22 +495,28 @@ pub fn parse_imports<'a>(
             &mut self,
             decl: &oxc_ast::ast::TSImportEqualsDeclaration<'a>,
         ) {
-            if !decl.import_kind.is_type() {
+            let kind = if decl.import_kind.is_type() {
+                ImportKind::Type
+            } else {
+                ImportKind::ImportEquals
+            };
+            if kind == ImportKind::ImportEquals || self.type_imports {
                 if let oxc_ast::ast::TSModuleReference::ExternalModuleReference(
                     external_module_reference,
                 ) = &decl.module_reference
                 {
-                    let specifier_literal = &external_module_reference.expression;
-                    self.specifiers
-                        .push((specifier_literal.value.as_str(), specifier_literal.span))
+                    self.push(&external_module_reference.expression, kind, decl.span);
+                    if kind == ImportKind::ImportEquals {
+                        self.bind(decl.id.name.as_str());
+                    }
+                    self.bind_import(decl.id.name.as_str(), None);
                 }
             };
             walk_ts_import_equals_declaration(self, decl)
         }
         fn visit_import_expression(&mut self, expr: &oxc_ast::ast::ImportExpression<'a>) {
             if let Expression::StringLiteral(string_literal) = &expr.source {
-                self.specifiers
-                    .push((string_literal.value.as_str(), string_literal.span))
+                self.push(string_literal, ImportKind::Dynamic, expr.span)
             } else {
                 self.non_literal_imports.push(expr.source.span())
             }
@@ -94,23 +528,62 @@ pub fn parse_imports<'a>(
                     if callee_id.name == "require" {
                         let arg = &expr.arguments[0];
                         if let Argument::StringLiteral(source) = arg {
-                            self.specifiers.push((source.value.as_str(), source.span));
+                            self.push(source, ImportKind::Require, expr.span);
                         } else {
                             self.non_literal_imports.push(arg.span());
                         }
                     }
                 };
             }
+            // Callbacks may be called right away, like the ones of `Array.prototype.map`
+            self.push_callee(Callee::of_expression(&expr.callee));
+            for arg in &expr.arguments {
+                self.push_callee(Callee::of_argument(arg));
+            }
             walk_call_expression(self, expr)
         }
     }
 
-    let mut visitor = ImportsVisitor::<'a>::default();
+    let mut visitor = ImportsVisitor::<'a> {
+        type_imports,
+        ..Default::default()
+    };
     walk_program(&mut visitor, &parse_return.program);
+    visitor.reexport_imported_bindings();
+    let running_scopes = visitor.running_scopes();
+    let top_level_references = visitor
+        .references
+        .iter()
+        .filter(|(scope, _)| running_scopes.contains(scope))
+        .map(|(_, name)| *name)
+        .collect::<HashSet<_>>();
+    for (name, index) in &visitor.bindings {
+        if top_level_references.contains(name) {
+            visitor.specifiers[*index].used_at_top_level = true;
+        }
+    }
+    for (index, scope) in &visitor.requires {
+        if running_scopes.contains(scope) {
+            visitor.specifiers[*index].used_at_top_level = true;
+        }
+    }
+    let ignored_lines = ignored_lines(source, &parse_return.trivias);
+    let specifiers = visitor
+        .specifiers
+        .into_iter()
+        .map(|specifier| Specifier {
+            ignore: ignored_lines
+                .iter()
+                .find(|(line, _)| line.contains(&specifier.statement.start))
+                .map(|(_, comment)| comment.clone()),
+            ..specifier
+        })
+        .collect();
     (
         Imports {
-            specifiers: visitor.specifiers,
+            specifiers,
             non_literal_imports: visitor.non_literal_imports,
+            exports: visitor.exports,
         },
         parse_return.errors,
     )
@@ -119,7 +592,7 @@ pub fn parse_imports<'a>(
 #[cfg(test)]
 mod tests {
 
-    use super::parse_imports;
+    use super::{parse_imports, ImportKind};
     use oxc_allocator::Allocator;
     use oxc_span::SourceType;
 
@@ -146,23 +619,283 @@ const d = import('d');
 const e = import('e' + d);
 const f = require('f');
 const g = require('g' + f);
+export * from 'h';
+export { i } from 'i';
+export type { j } from 'j';
 ";
-        let imports = parse_imports(&allocator, SourceType::default().with_module(true), src).0;
+        let imports = parse_imports(
+            &allocator,
+            SourceType::default()
+                .with_typescript(true)
+                .with_module(true),
+            src,
+            false,
+        )
+        .0;
         assert_eq!(
             imports
                 .specifiers
                 .into_iter()
-                .map(|(s, _)| s)
+                .map(|specifier| (specifier.value, specifier.kind))
                 .collect::<Vec<_>>(),
-            vec!["foo", "a", "c", "d", "f"]
+            vec![
+                ("foo", ImportKind::Static),
+                ("a", ImportKind::Static),
+                ("c", ImportKind::ImportEquals),
+                ("d", ImportKind::Dynamic),
+                ("f", ImportKind::Require),
+                ("h", ImportKind::ReExport),
+                ("i", ImportKind::ReExport),
+            ]
         );
         assert_eq!(
             imports
                 .non_literal_imports
                 .into_iter()
-                .map(|span| span.source_text(&src))
+                .map(|span| span.source_text(src))
                 .collect::<Vec<&str>>(),
             vec!["'e' + d", "'g' + f"]
         )
     }
+
+    #[test]
+    fn test_type_imports() {
+        let allocator = Allocator::default();
+        let src = "import a, { type A } from 'a';
+import type { B } from 'b';
+import type C = require('c');
+export type { D } from 'd';
+export type * from 'e';
+import { type F } from 'f';
+export { g, type G } from 'g';
+const x: B = a;
+";
+        let imports = parse_imports(
+            &allocator,
+            SourceType::default()
+                .with_typescript(true)
+                .with_module(true),
+            src,
+            true,
+        )
+        .0;
+        assert_eq!(
+            imports
+                .specifiers
+                .into_iter()
+                .map(|specifier| (
+                    specifier.value,
+                    specifier.kind,
+                    specifier.names,
+                    specifier.used_at_top_level
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("a", ImportKind::Static, Some(vec!["default"]), true),
+                ("a", ImportKind::Type, Some(vec!["A"]), false),
+                ("b", ImportKind::Type, Some(vec!["B"]), false),
+                ("c", ImportKind::Type, None, false),
+                ("d", ImportKind::Type, Some(vec!["D"]), false),
+                ("e", ImportKind::Type, None, false),
+                ("f", ImportKind::Type, Some(vec!["F"]), false),
+                ("g", ImportKind::ReExport, Some(vec!["g"]), false),
+                ("g", ImportKind::Type, Some(vec!["G"]), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ignore_comments() {
+        let allocator = Allocator::default();
+        let src = "import 'a';
+// decycle-ignore-next-line
+import 'b';
+// decycle-ignore-next-line lazy loaded to break the cycle
+const c = () => import('c');
+/* decycle-ignore-next-line */ import 'd';
+// decycle-ignore-next-lines
+import 'e';
+// decycle-ignore-next-line
+
+import 'f';
+";
+        let imports = parse_imports(
+            &allocator,
+            SourceType::default().with_module(true),
+            src,
+            false,
+        )
+        .0;
+        assert_eq!(
+            imports
+                .specifiers
+                .into_iter()
+                .map(|specifier| (
+                    specifier.value,
+                    specifier.ignore.map(|comment| comment.reason)
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("a", None),
+                ("b", Some(None)),
+                ("c", Some(Some("lazy loaded to break the cycle".to_owned()))),
+                ("d", None),
+                ("e", None),
+                ("f", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_used_at_top_level() {
+        let src = "import a from 'a';
+import { b } from 'b';
+import * as c from 'c';
+import { d, type D } from 'd';
+import { e } from 'e';
+import { f } from 'f';
+import g = require('g');
+import 'h';
+export { i } from 'i';
+const x = a + 1;
+function run(y = b) { return c.value; }
+let z: D;
+class K extends e {
+    static s = f;
+    t = b;
+    method() { return g; }
+}
+const l = () => require('l');
+const m = require('m');
+";
+        assert_eq!(used_at_top_level(src), vec!["a", "e", "f", "m"]);
+
+        // Functions called while the file is evaluated, directly or as callbacks
+        let src = "import { a } from 'a';
+import { b } from 'b';
+import { c } from 'c';
+import { d } from 'd';
+import { e } from 'e';
+import { f } from 'f';
+import { g } from 'g';
+export const x = (() => a + 1)();
+function init() { return helper(); }
+function helper() { return b; }
+init();
+const later = () => c;
+[1].map(function () { return d; });
+new Promise(() => e);
+const run = () => f;
+[1].forEach(run);
+export function notCalled() { return g; }
+";
+        assert_eq!(used_at_top_level(src), vec!["a", "b", "d", "e", "f"]);
+    }
+
+    fn used_at_top_level(src: &str) -> Vec<String> {
+        let allocator = Allocator::default();
+        let imports = parse_imports(
+            &allocator,
+            SourceType::default()
+                .with_typescript(true)
+                .with_module(true),
+            src,
+            false,
+        )
+        .0;
+        imports
+            .specifiers
+            .iter()
+            .filter(|specifier| specifier.used_at_top_level)
+            .map(|specifier| specifier.value.to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_names_and_exports() {
+        let allocator = Allocator::default();
+        let src = "import a, { b as c, type T } from 'a';
+import * as d from 'd';
+import 'e';
+import {} from 'f';
+export * from 'g';
+export * as h from 'h';
+export { i, j as k, type U } from 'i';
+const l = 1;
+export { l, l as m };
+export const [n, { o }] = [];
+export function p() {}
+export interface Q {}
+export enum R {}
+export default class {}
+";
+        let imports = parse_imports(
+            &allocator,
+            SourceType::default()
+                .with_typescript(true)
+                .with_module(true),
+            src,
+            false,
+        )
+        .0;
+        assert_eq!(
+            imports
+                .specifiers
+                .iter()
+                .map(|specifier| (specifier.value, specifier.names.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("a", Some(vec!["default", "b"])),
+                ("d", None),
+                ("e", None),
+                ("f", None),
+                ("g", None),
+                ("h", None),
+                ("i", Some(vec!["i", "j"])),
+            ]
+        );
+        let exports = imports.exports;
+        assert_eq!(
+            exports.local,
+            vec!["l", "m", "n", "o", "p", "Q", "R", "default"]
+        );
+        assert_eq!(
+            exports.reexported,
+            vec![("h", 5, None), ("i", 6, Some("i")), ("k", 6, Some("j"))]
+        );
+        assert_eq!(exports.star_reexported, vec![4]);
+    }
+
+    #[test]
+    fn test_exported_imports() {
+        let allocator = Allocator::default();
+        let src = "export { a, c as f, d };
+import a, { b as c } from 'a';
+import * as d from 'd';
+import e = require('e');
+export default e;
+const g = 1;
+export { g };
+";
+        let exports = parse_imports(
+            &allocator,
+            SourceType::default()
+                .with_typescript(true)
+                .with_module(true),
+            src,
+            false,
+        )
+        .0
+        .exports;
+        assert_eq!(exports.local, vec!["g"]);
+        assert_eq!(
+            exports.reexported,
+            vec![
+                ("a", 0, Some("default")),
+                ("f", 0, Some("b")),
+                ("d", 1, None),
+                ("default", 2, None)
+            ]
+        );
+    }
 }
@@ -1,19 +1,19 @@
 pub mod algorithms;
+mod cli;
 mod collect_deps;
 mod dep_graph;
 pub mod hash;
 mod js_resolver;
 
 pub use collect_deps::collect_dependencies;
-pub use js_resolver::JsDiscoverDependency;
-use oxc_resolver::{FileMetadata, FileSystem, ResolveOptions, ResolverGeneric};
+pub use js_resolver::{
+    BareSpecifiers, IgnoreComment, ImportKind, ImportedNames, JsDiscoverDependency, JsImport,
+};
+use oxc_resolver::{FileMetadata, FileSystem};
 
-use clap::Parser;
-use rayon::iter::{ParallelBridge, ParallelIterator};
 use std::{
     io,
     path::{Path, PathBuf},
-    sync::{mpsc, Arc},
 };
 
 #[derive(Default, Clone, Debug)]
@@ -38,4 +38,4 @@ impl FileSystem for OsFileSystem {
 }
 
 #[doc(hidden)]
-pub fn run(args: &[&str], cwd: &Path) {}
+pub use cli::run;
@@ -0,0 +1,1361 @@
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+fn fixture(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    for (path, content) in files {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    dir
+}
+
+/// Writes the files `0.js` to `{file_count - 1}.js`, each importing all the others.
+fn mesh_fixture(name: &str, file_count: usize) -> PathBuf {
+    let files = (0..file_count)
+        .map(|i| {
+            let imports = (0..file_count)
+                .filter(|j| *j != i)
+                .map(|j| format!("import './{j}';"))
+                .collect::<String>();
+            (format!("{i}.js"), imports)
+        })
+        .collect::<Vec<_>>();
+    let files = files
+        .iter()
+        .map(|(path, content)| (path.as_str(), content.as_str()))
+        .collect::<Vec<_>>();
+    fixture(name, &files)
+}
+
+fn run(args: &[&str], cwd: &Path) -> (ExitCode, String) {
+    let mut stdout = Vec::<u8>::new();
+    let exit_code = decycle::run(args, cwd, &mut stdout);
+    (exit_code, String::from_utf8(stdout).unwrap())
+}
+
+#[test]
+fn test_check() {
+    let cwd = fixture(
+        "check",
+        &[
+            ("a.js", "import './b'"),
+            ("b.ts", "import './a'"),
+            ("c.js", "import './b'"),
+        ],
+    );
+    let (exit_code, stdout) = run(&["decycle", "check", "c.js"], &cwd);
+    assert_eq!(exit_code, ExitCode::FAILURE);
+    assert_eq!(
+        stdout,
+        "Found 2 new imports in cycles:
+  a.js -> b.ts
+    in a cycle of length 2: a.js -> b.ts -> a.js
+    a.js:1:8: import './b'
+  b.ts -> a.js
+    in a cycle of length 2: b.ts -> a.js -> b.ts
+    b.ts:1:8: import './a'
+"
+    );
+
+    let (exit_code, _) = run(
+        &["decycle", "check", "a.js"],
+        &fixture("check-acyclic", &[("a.js", "import './b'"), ("b.js", "")]),
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+}
+
+#[test]
+fn test_snapshot() {
+    let cwd = fixture(
+        "snapshot",
+        &[("a.js", "import './b'"), ("b.ts", "import './a'")],
+    );
+    let (exit_code, _) = run(
+        &[
+            "decycle",
+            "snapshot",
+            "a.js",
+            "--snapshot",
+            "snapshots/cycles.json",
+        ],
+        &cwd,
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    let snapshot: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(cwd.join("snapshots/cycles.json")).unwrap())
+            .unwrap();
+    assert_eq!(
+        snapshot,
+        serde_json::json!({
+            "version": 1,
+            "entries": ["a.js"],
+            "resolver": {
+                "extensions": [".js", ".jsx", ".ts", ".tsx", ".node", ".json"],
+                "conditions": [],
+                "alias": {},
+                "bareSpecifiers": false,
+                "tsconfig": null,
+                "externalDirectories": ["node_modules"],
+                "workspaces": false
+            },
+            "edges": [
+                { "source": "a.js", "target": "b.ts" },
+                { "source": "b.ts", "target": "a.js" }
+            ]
+        })
+    );
+    assert_eq!(
+        run(
+            &[
+                "decycle",
+                "check",
+                "a.js",
+                "--snapshot",
+                "snapshots/cycles.json"
+            ],
+            &cwd
+        ),
+        (ExitCode::SUCCESS, String::new())
+    );
+
+    // The entries are stored relative to the working directory, like the edges
+    let entry = cwd.join("snapshots/../a.js");
+    let (exit_code, _) = run(
+        &[
+            "decycle",
+            "snapshot",
+            entry.to_str().unwrap(),
+            "--snapshot",
+            "snapshots/cycles.json",
+        ],
+        &cwd,
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    let snapshot: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(cwd.join("snapshots/cycles.json")).unwrap())
+            .unwrap();
+    assert_eq!(snapshot["entries"], serde_json::json!(["a.js"]));
+}
+
+#[test]
+fn test_check_unversioned_snapshot() {
+    let cwd = fixture(
+        "check-unversioned-snapshot",
+        &[
+            ("a.js", "import './b'"),
+            ("b.ts", "import './a'"),
+            (
+                "cyclepath-snapshot.json",
+                r#"[["a.js", "b.ts"], ["b.ts", "a.js"]]"#,
+            ),
+        ],
+    );
+    assert_eq!(
+        run(&["decycle", "check", "a.js"], &cwd),
+        (ExitCode::SUCCESS, String::new())
+    );
+}
+
+#[test]
+fn test_check_snapshot() {
+    let cwd = fixture(
+        "check-snapshot",
+        &[
+            ("a.js", "import './b'"),
+            ("b.js", "import './a'; import './c'"),
+            ("c.js", ""),
+        ],
+    );
+    assert_eq!(
+        run(&["decycle", "snapshot", "a.js"], &cwd).0,
+        ExitCode::SUCCESS
+    );
+    assert_eq!(
+        run(&["decycle", "check", "a.js"], &cwd),
+        (ExitCode::SUCCESS, String::new())
+    );
+
+    std::fs::write(cwd.join("c.js"), "import './b'").unwrap();
+    let (exit_code, stdout) = run(&["decycle", "check", "a.js"], &cwd);
+    assert_eq!(exit_code, ExitCode::FAILURE);
+    assert_eq!(
+        stdout,
+        "Found 2 new imports in cycles:
+  b.js -> c.js
+    in a cycle of length 2: b.js -> c.js -> b.js
+    b.js:1:22: import './a'; import './c'
+  c.js -> b.js
+    in a cycle of length 2: c.js -> b.js -> c.js
+    c.js:1:8: import './b'
+"
+    );
+
+    std::fs::write(cwd.join("b.js"), "import './c'").unwrap();
+    let (exit_code, stdout) = run(&["decycle", "check", "a.js"], &cwd);
+    assert_eq!(exit_code, ExitCode::FAILURE);
+    assert_eq!(
+        stdout,
+        "2 imports in the snapshot are no longer in cycles, run `decycle snapshot` to remove them:
+  a.js -> b.js
+  b.js -> a.js
+Found 2 new imports in cycles:
+  b.js -> c.js
+    in a cycle of length 2: b.js -> c.js -> b.js
+    b.js:1:8: import './c'
+  c.js -> b.js
+    in a cycle of length 2: c.js -> b.js -> c.js
+    c.js:1:8: import './b'
+"
+    );
+
+    std::fs::write(cwd.join("c.js"), "").unwrap();
+    let (exit_code, stdout) = run(&["decycle", "check", "a.js"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(
+        stdout,
+        "2 imports in the snapshot are no longer in cycles, run `decycle snapshot` to remove them:
+  a.js -> b.js
+  b.js -> a.js
+"
+    );
+}
+
+#[test]
+fn test_multiline_import_text() {
+    let cwd = fixture(
+        "multiline-import-text",
+        &[
+            ("a.ts", "import {\n  b,\n} from './b';\nexport const a = 1;"),
+            ("b.ts", "export { a as b } from './a';"),
+        ],
+    );
+    let (exit_code, stdout) = run(&["decycle", "check", "a.ts"], &cwd);
+    assert_eq!(exit_code, ExitCode::FAILURE);
+    assert_eq!(
+        stdout,
+        "Found 2 new imports in cycles:
+  a.ts -> b.ts
+    in a cycle of length 2: a.ts -> b.ts -> a.ts
+    a.ts:3:8: import { b, } from './b';
+  b.ts -> a.ts
+    in a cycle of length 2: b.ts -> a.ts -> b.ts
+    b.ts:1:24: export { a as b } from './a';
+"
+    );
+}
+
+#[test]
+fn test_cycles() {
+    let cwd = fixture(
+        "cycles",
+        &[
+            ("a.js", "import './b'; import './c'"),
+            ("b.js", "import './a'"),
+            ("c.js", "import './b'"),
+        ],
+    );
+    let (exit_code, stdout) = run(&["decycle", "cycles", "a.js"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(
+        stdout,
+        "a.js -> b.js -> a.js
+    a.js:1:8: import './b'; import './c'
+    b.js:1:8: import './a'
+a.js -> c.js -> b.js -> a.js
+    a.js:1:22: import './b'; import './c'
+    c.js:1:8: import './b'
+    b.js:1:8: import './a'
+Found 2 cycles
+"
+    );
+
+    let (exit_code, stdout) = run(
+        &["decycle", "cycles", "a.js", "--max-cycle-length", "2"],
+        &cwd,
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert!(stdout.starts_with("a.js -> b.js -> a.js\n"));
+    assert!(stdout.ends_with("Found 1 cycle, longer ones were skipped\n"));
+
+    let (exit_code, stdout) = run(&["decycle", "cycles", "a.js", "--max-cycles", "1"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert!(stdout.starts_with("a.js -> b.js -> a.js\n"));
+    assert!(stdout.ends_with("Found 1 cycle, stopped at the maximum count\n"));
+
+    // The cycles of a large tangle are only searched within the default limits
+    let (exit_code, stdout) = run(
+        &["decycle", "cycles", "0.js"],
+        &mesh_fixture("cycles-mesh", 10),
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert!(stdout.ends_with("Found 100000 cycles, stopped at the maximum count\n"));
+}
+
+#[test]
+fn test_graph() {
+    let cwd = fixture(
+        "graph",
+        &[
+            ("a.js", "import './b'"),
+            ("b.js", "require('./c')"),
+            ("c.js", ""),
+        ],
+    );
+    let (exit_code, stdout) = run(&["decycle", "graph", "a.js"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(stdout, "a.js -> b.js\nb.js -> c.js\n3 files, 2 imports\n");
+}
+
+#[test]
+fn test_invalid_args() {
+    let (exit_code, _) = run(&["decycle", "unknown"], Path::new("/"));
+    assert_eq!(exit_code, ExitCode::from(2));
+
+    #[cfg(unix)]
+    {
+        use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+        let args = [
+            OsString::from("decycle"),
+            OsString::from("cycles"),
+            OsString::from_vec(b"\xff.js".to_vec()),
+        ];
+        let exit_code = decycle::run(args, Path::new("/"), &mut Vec::new());
+        assert_eq!(exit_code, ExitCode::from(2));
+    }
+}
+
+#[test]
+fn test_multiple_entries() {
+    let cwd = fixture(
+        "multiple-entries",
+        &[
+            ("src/pages/a.tsx", "import '../lib/x'"),
+            ("src/pages/b.tsx", "import '../lib/y'"),
+            ("src/pages/c.test.tsx", "import '../lib/z'"),
+            ("src/lib/x.ts", ""),
+            ("src/lib/y.ts", ""),
+            ("src/lib/z.ts", ""),
+            ("worker.js", "import './src/pages/a'"),
+        ],
+    );
+    let (exit_code, stdout) = run(
+        &[
+            "decycle",
+            "graph",
+            "./worker.js",
+            "--include",
+            "src/pages/**/*.tsx",
+            "--exclude",
+            "**/*.test.tsx",
+        ],
+        &cwd,
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(
+        stdout,
+        "src/pages/a.tsx -> src/lib/x.ts
+src/pages/b.tsx -> src/lib/y.ts
+worker.js -> src/pages/a.tsx
+5 files, 3 imports
+"
+    );
+
+    let (exit_code, _) = run(&["decycle", "graph", "--include", "*.vue"], &cwd);
+    assert_eq!(exit_code, ExitCode::from(2));
+}
+
+#[test]
+fn test_cycles_json() {
+    let cwd = fixture(
+        "cycles-json",
+        &[
+            ("a.js", "import './b';\nimport './missing';"),
+            ("b.js", "import './a'; import './c'"),
+            ("c.js", ""),
+        ],
+    );
+    let (exit_code, stdout) = run(&["decycle", "cycles", "a.js", "--format", "json"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    let report: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let a_to_b = serde_json::json!({
+        "source": "a.js",
+        "target": "b.js",
+        "imports": [{ "start": 7, "end": 12, "line": 1, "column": 8, "endLine": 1, "endColumn": 13, "text": "import './b';" }],
+        "usedAtTopLevel": false
+    });
+    let b_to_a = serde_json::json!({
+        "source": "b.js",
+        "target": "a.js",
+        "imports": [{ "start": 7, "end": 12, "line": 1, "column": 8, "endLine": 1, "endColumn": 13, "text": "import './a'; import './c'" }],
+        "usedAtTopLevel": false
+    });
+    let with_shortest_cycle = |edge: &serde_json::Value, cycle: &[&str]| {
+        let mut edge = edge.clone();
+        edge["shortestCycle"] = serde_json::json!(cycle);
+        edge
+    };
+    assert_eq!(
+        report,
+        serde_json::json!({
+            "version": 1,
+            "stats": {
+                "files": 3,
+                "imports": 3,
+                "cycleEdges": 2,
+                "cycles": 1,
+                "filesWithErrors": 1
+            },
+            "cycleEdges": [
+                with_shortest_cycle(&a_to_b, &["a.js", "b.js"]),
+                with_shortest_cycle(&b_to_a, &["b.js", "a.js"]),
+            ],
+            "cycles": [[a_to_b, b_to_a]],
+            "cycleSearch": "complete",
+            "errors": [{
+                "path": "a.js",
+                "errors": [{
+                    "kind": "resolve",
+                    "message": "Cannot find module './missing'",
+                    "location": {
+                        "start": 21,
+                        "end": 32,
+                        "line": 2,
+                        "column": 8,
+                        "endLine": 2,
+                        "endColumn": 19,
+                        "text": "import './missing';"
+                    }
+                }]
+            }],
+            "ignoredImports": []
+        })
+    );
+}
+
+#[test]
+fn test_cycles_sarif() {
+    let cwd = fixture(
+        "cycles-sarif",
+        &[("a.js", "import './b';"), ("b.js", "\n  require('./a')")],
+    );
+    let (exit_code, stdout) = run(&["decycle", "cycles", "a.js", "--format", "sarif"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    let sarif: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let sarif_run = &sarif["runs"][0];
+    assert_eq!(sarif["version"], "2.1.0");
+    assert_eq!(sarif_run["tool"]["driver"]["name"], "decycle");
+    assert_eq!(
+        sarif_run["tool"]["driver"]["rules"][0]["id"],
+        "import-cycle"
+    );
+    assert_eq!(
+        sarif_run["results"],
+        serde_json::json!([
+            {
+                "ruleId": "import-cycle",
+                "level": "error",
+                "message": { "text": "The import of `b.js` is part of an import cycle: a.js -> b.js -> a.js." },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": "a.js", "uriBaseId": "%SRCROOT%" },
+                        "region": { "startLine": 1, "startColumn": 8, "endLine": 1, "endColumn": 13 }
+                    }
+                }]
+            },
+            {
+                "ruleId": "import-cycle",
+                "level": "error",
+                "message": { "text": "The import of `a.js` is part of an import cycle: b.js -> a.js -> b.js." },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": "b.js", "uriBaseId": "%SRCROOT%" },
+                        "region": { "startLine": 2, "startColumn": 11, "endLine": 2, "endColumn": 16 }
+                    }
+                }]
+            }
+        ])
+    );
+
+    // The imports of a large tangle are reported without enumerating its cycles
+    let (exit_code, stdout) = run(
+        &["decycle", "cycles", "0.js", "--format", "sarif"],
+        &mesh_fixture("cycles-sarif-mesh", 30),
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    let sarif: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(
+        sarif["runs"][0]["results"].as_array().unwrap().len(),
+        30 * 29
+    );
+}
+
+#[test]
+fn test_graph_export() {
+    let cwd = fixture(
+        "graph-export",
+        &[
+            ("a.js", "import './b'"),
+            ("b.js", "import './c'"),
+            ("c.js", "import './b'; import './d'"),
+            ("d.js", "import './e'"),
+            ("e.js", ""),
+        ],
+    );
+    let (exit_code, stdout) = run(&["decycle", "graph", "a.js", "--format", "dot"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(
+        stdout,
+        r#"digraph {
+    "a.js";
+    "b.js";
+    "c.js";
+    "d.js";
+    "e.js";
+    "a.js" -> "b.js";
+    "b.js" -> "c.js" [color=red];
+    "c.js" -> "b.js" [color=red];
+    "c.js" -> "d.js";
+    "d.js" -> "e.js";
+}
+"#
+    );
+
+    let (exit_code, stdout) = run(
+        &[
+            "decycle",
+            "graph",
+            "a.js",
+            "--format",
+            "mermaid",
+            "--cycles-only",
+        ],
+        &cwd,
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(
+        stdout,
+        r#"flowchart LR
+    n0["b.js"]
+    n1["c.js"]
+    n0 --> n1
+    n1 --> n0
+    linkStyle 0,1 stroke:red
+"#
+    );
+
+    let (exit_code, stdout) = run(&["decycle", "graph", "a.js", "--around", "./d.js"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(stdout, "c.js -> d.js\nd.js -> e.js\n3 files, 2 imports\n");
+
+    let (exit_code, stdout) = run(
+        &[
+            "decycle", "graph", "a.js", "--around", "d.js", "--depth", "2",
+        ],
+        &cwd,
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(
+        stdout,
+        "b.js -> c.js\nc.js -> b.js\nc.js -> d.js\nd.js -> e.js\n4 files, 4 imports\n"
+    );
+
+    let around = cwd.join("d.js");
+    let (exit_code, stdout) = run(
+        &[
+            "decycle",
+            "graph",
+            "a.js",
+            "--around",
+            around.to_str().unwrap(),
+        ],
+        &cwd,
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(stdout, "c.js -> d.js\nd.js -> e.js\n3 files, 2 imports\n");
+
+    let (exit_code, _) = run(&["decycle", "graph", "a.js", "--around", "x.js"], &cwd);
+    assert_eq!(exit_code, ExitCode::from(2));
+}
+
+#[test]
+fn test_components() {
+    let cwd = fixture(
+        "components",
+        &[
+            ("a.js", "import './b'; import './e'"),
+            ("b.js", "import './c'"),
+            ("c.js", "import './a'; import './b'"),
+            ("d.js", "import './a'"),
+            ("e.js", "import './e'"),
+        ],
+    );
+    let (exit_code, stdout) = run(&["decycle", "components", "d.js"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(
+        stdout,
+        "Component 1: 3 files, 4 imports
+    a.js
+    b.js
+    c.js
+Component 2: 1 file, 1 import
+    e.js
+Found 2 components with cycles
+"
+    );
+
+    let (exit_code, stdout) = run(&["decycle", "components", "d.js", "--format", "json"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!([
+            { "size": 3, "files": ["a.js", "b.js", "c.js"], "imports": 4 },
+            { "size": 1, "files": ["e.js"], "imports": 1 },
+        ])
+    );
+}
+
+#[test]
+fn test_cut() {
+    let cwd = fixture(
+        "cut",
+        &[
+            ("a.js", "import './b'; import './d'"),
+            ("b.js", "import './c'"),
+            ("c.js", "import './a'"),
+            ("d.js", "import './c'"),
+            ("e.js", "import './e'"),
+            ("main.js", "import './a'; import './e'"),
+        ],
+    );
+    let (exit_code, stdout) = run(&["decycle", "cut", "main.js"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(
+        stdout,
+        "Removing 2 imports breaks all the cycles:
+  c.js -> a.js, in 2 of 3 cycles
+    c.js:1:8: import './a'
+  e.js -> e.js, in 1 of 3 cycles
+    e.js:1:8: import './e'
+"
+    );
+
+    let (exit_code, stdout) = run(&["decycle", "cut", "main.js", "--max-cycles", "1"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert!(
+        stdout.ends_with("The search for cycles stopped early, so the counts are lower bounds\n")
+    );
+
+    // The cycles of a large tangle are only searched within the default limits
+    let (exit_code, stdout) = run(&["decycle", "cut", "0.js"], &mesh_fixture("cut-mesh", 10));
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert!(stdout.contains("of 100000 cycles"));
+
+    let (exit_code, stdout) = run(
+        &["decycle", "cut", "a.js"],
+        &fixture("cut-acyclic", &[("a.js", "import './b'"), ("b.js", "")]),
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(stdout, "Found no cycles\n");
+}
+
+#[test]
+fn test_explain() {
+    let cwd = fixture(
+        "explain",
+        &[
+            ("a.js", "import './b';\nimport './c';"),
+            ("b.js", "export {} from './d'"),
+            ("c.js", "import './d'"),
+            ("d.js", "import './a'"),
+            ("e.js", "import './a'"),
+        ],
+    );
+    let (exit_code, stdout) = run(&["decycle", "explain", "./a.js", "d.js"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(
+        stdout,
+        "a.js reaches d.js in 2 imports:
+  a.js -> b.js
+    a.js:1:8: import './b';
+  b.js -> d.js
+    b.js:1:16: export {} from './d'
+d.js reaches a.js in 1 import:
+  d.js -> a.js
+    d.js:1:8: import './a'
+"
+    );
+
+    let (exit_code, stdout) = run(&["decycle", "explain", "a.js", "e.js"], &cwd);
+    assert_eq!(exit_code, ExitCode::FAILURE);
+    assert_eq!(
+        stdout,
+        "a.js doesn't import e.js, directly or indirectly
+e.js reaches a.js in 1 import:
+  e.js -> a.js
+    e.js:1:8: import './a'
+"
+    );
+
+    // Absolute paths and paths going through `..` name the same files
+    let (from, to) = (cwd.join("a.js"), cwd.join("x/../e.js"));
+    let (exit_code, absolute_stdout) = run(
+        &[
+            "decycle",
+            "explain",
+            from.to_str().unwrap(),
+            to.to_str().unwrap(),
+        ],
+        &cwd,
+    );
+    assert_eq!(exit_code, ExitCode::FAILURE);
+    assert_eq!(absolute_stdout, stdout);
+
+    // The imports are filtered like in the other commands
+    let cwd = fixture(
+        "explain-dynamic-imports",
+        &[
+            ("a.js", "import './b'"),
+            ("b.js", "const a = import('./a')"),
+        ],
+    );
+    let (exit_code, _) = run(&["decycle", "explain", "a.js", "b.js"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    let (exit_code, stdout) = run(
+        &[
+            "decycle",
+            "explain",
+            "a.js",
+            "b.js",
+            "--ignore-dynamic-imports",
+        ],
+        &cwd,
+    );
+    assert_eq!(exit_code, ExitCode::FAILURE);
+    assert!(stdout.ends_with("b.js doesn't import a.js, directly or indirectly\n"));
+}
+
+#[test]
+fn test_bare_specifiers() {
+    let cwd = fixture(
+        "bare-specifiers",
+        &[
+            (
+                "tsconfig.json",
+                r#"{ "compilerOptions": { "baseUrl": ".", "paths": { "@/*": ["src/*"] } } }"#,
+            ),
+            ("src/a.ts", "import '@/b'; import 'fs'; import 'node:path'"),
+            ("src/b.ts", "import './a'; import 'pkg'"),
+            ("node_modules/pkg/package.json", r#"{ "main": "index.js" }"#),
+            ("node_modules/pkg/index.js", ""),
+        ],
+    );
+    let (exit_code, stdout) = run(&["decycle", "graph", "src/a.ts"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(stdout, "1 file, 0 imports\n");
+
+    let (exit_code, stdout) = run(&["decycle", "graph", "src/a.ts", "--bare-specifiers"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(
+        stdout,
+        "src/a.ts -> src/b.ts\nsrc/b.ts -> src/a.ts\n2 files, 2 imports\n"
+    );
+
+    let (exit_code, stdout) = run(
+        &[
+            "decycle",
+            "graph",
+            "src/a.ts",
+            "--bare-specifiers",
+            "--external-dir",
+            "vendor",
+        ],
+        &cwd,
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert!(stdout.contains("src/b.ts -> node_modules/pkg/index.js\n"));
+}
+
+#[test]
+fn test_workspaces() {
+    let cwd = fixture(
+        "workspaces",
+        &[
+            ("package.json", r#"{ "workspaces": ["packages/*"] }"#),
+            (
+                "packages/a/package.json",
+                r#"{ "name": "@m/a", "main": "dist/index.js" }"#,
+            ),
+            ("packages/a/src/index.ts", "import '@m/b'"),
+            ("packages/a/src/util.ts", ""),
+            ("packages/a/dist/index.js", ""),
+            (
+                "packages/b/package.json",
+                r#"{ "name": "@m/b", "source": "lib/main.ts" }"#,
+            ),
+            (
+                "packages/b/lib/main.ts",
+                "import '@m/a'; import '@m/a/src/util'",
+            ),
+            // Linked by the package manager
+            (
+                "node_modules/@m/a/package.json",
+                r#"{ "name": "@m/a", "main": "dist/index.js" }"#,
+            ),
+            ("node_modules/@m/a/dist/index.js", ""),
+        ],
+    );
+    let (exit_code, stdout) = run(
+        &[
+            "decycle",
+            "graph",
+            "packages/a/src/index.ts",
+            "--bare-specifiers",
+            "--workspaces",
+        ],
+        &cwd,
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(
+        stdout,
+        "packages/a/src/index.ts -> packages/b/lib/main.ts
+packages/b/lib/main.ts -> packages/a/src/index.ts
+packages/b/lib/main.ts -> packages/a/src/util.ts
+3 files, 3 imports
+"
+    );
+
+    // Workspaces imply bare specifiers
+    let (exit_code, workspaces_stdout) = run(
+        &[
+            "decycle",
+            "graph",
+            "packages/a/src/index.ts",
+            "--workspaces",
+        ],
+        &cwd,
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(workspaces_stdout, stdout);
+
+    let (exit_code, stdout) = run(
+        &[
+            "decycle",
+            "graph",
+            "packages/a/src/index.ts",
+            "--bare-specifiers",
+        ],
+        &cwd,
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(stdout, "1 file, 0 imports\n");
+}
+
+#[test]
+fn test_packages() {
+    let cwd = fixture(
+        "packages",
+        &[
+            ("main.ts", "import './a/x'; import './b/z'"),
+            ("a/package.json", r#"{ "name": "a" }"#),
+            ("a/x.ts", "import '../b/y'; import '../b/u'"),
+            ("a/w.ts", "import './x'"),
+            ("b/package.json", r#"{ "name": "b" }"#),
+            ("b/y.ts", ""),
+            ("b/u.ts", ""),
+            ("b/z.ts", "import '../a/w'"),
+        ],
+    );
+    let (exit_code, stdout) = run(&["decycle", "cycles", "main.ts"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(stdout, "Found 0 cycles\n");
+
+    let (exit_code, stdout) = run(&["decycle", "packages", "main.ts"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(
+        stdout,
+        "Found 2 package dependencies in cycles:
+  a -> b
+    in a cycle of length 2: a -> b -> a
+    a/x.ts -> b/u.ts
+    a/x.ts -> b/y.ts
+  b -> a
+    in a cycle of length 2: b -> a -> b
+    b/z.ts -> a/w.ts
+"
+    );
+
+    let (exit_code, stdout) = run(
+        &[
+            "decycle", "packages", "main.ts", "--group", "a=a/**", "--group", "b=b/y.ts",
+        ],
+        &cwd,
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(stdout, "Found no cycles between packages\n");
+
+    let (exit_code, _) = run(&["decycle", "packages", "main.ts", "--group", "a"], &cwd);
+    assert_eq!(exit_code, ExitCode::from(2));
+}
+
+#[test]
+fn test_layers() {
+    let cwd = fixture(
+        "layers",
+        &[
+            (
+                "decycle.json",
+                r#"{
+                    "layers": [
+                        { "name": "ui", "include": ["src/ui/**"], "mayImport": ["domain"] },
+                        { "name": "domain", "include": ["src/domain/**"] }
+                    ]
+                }"#,
+            ),
+            ("src/main.ts", "import './ui/app'"),
+            (
+                "src/ui/app.ts",
+                "import './button'\nimport '../domain/user'",
+            ),
+            ("src/ui/button.ts", "import '../utils'"),
+            (
+                "src/domain/user.ts",
+                "import '../utils'\nimport '../ui/button'",
+            ),
+            ("src/utils.ts", ""),
+        ],
+    );
+    let (exit_code, stdout) = run(&["decycle", "layers", "src/main.ts"], &cwd);
+    assert_eq!(exit_code, ExitCode::FAILURE);
+    assert_eq!(
+        stdout,
+        "Found 1 import breaking the layer rules:
+  src/domain/user.ts -> src/ui/button.ts (domain may not import ui)
+    src/domain/user.ts:2:8: import '../ui/button'
+"
+    );
+
+    std::fs::write(
+        cwd.join("allow-all.json"),
+        r#"{ "layers": [{ "name": "src", "include": ["src/**"] }] }"#,
+    )
+    .unwrap();
+    let (exit_code, stdout) = run(
+        &[
+            "decycle",
+            "layers",
+            "src/main.ts",
+            "--config",
+            "allow-all.json",
+        ],
+        &cwd,
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(stdout, "Found no imports breaking the layer rules\n");
+
+    std::fs::write(
+        cwd.join("unknown.json"),
+        r#"{ "layers": [{ "name": "ui", "include": ["src/ui/**"], "mayImport": ["db"] }] }"#,
+    )
+    .unwrap();
+    let (exit_code, _) = run(
+        &[
+            "decycle",
+            "layers",
+            "src/main.ts",
+            "--config",
+            "unknown.json",
+        ],
+        &cwd,
+    );
+    assert_eq!(exit_code, ExitCode::from(2));
+
+    std::fs::write(cwd.join("empty.json"), "{}").unwrap();
+    let (exit_code, _) = run(
+        &["decycle", "layers", "src/main.ts", "--config", "empty.json"],
+        &cwd,
+    );
+    assert_eq!(exit_code, ExitCode::from(2));
+}
+
+#[test]
+fn test_config() {
+    let cwd = fixture(
+        "config",
+        &[
+            (
+                "decycle.json",
+                r#"{
+                    "entries": ["main.ts"],
+                    "resolver": { "bareSpecifiers": true, "alias": { "@": "./src" } },
+                    "ignore": [{ "source": "src/legacy.ts", "target": "**" }],
+                    "format": "json",
+                    "limits": { "maxCycleLength": 2 }
+                }"#,
+            ),
+            ("main.ts", "import '@/a'; import './src/legacy'"),
+            ("src/a.ts", "import '@/b'"),
+            ("src/b.ts", "import './a'; import './c'"),
+            ("src/c.ts", "import './a'"),
+            (
+                "src/legacy.ts",
+                "import './a'; import './main'; import './old'",
+            ),
+            ("src/main.ts", "import './legacy'"),
+            ("src/old.ts", "import './missing'"),
+        ],
+    );
+    // old.ts is only imported by an ignored import, so its error isn't reported
+    let (exit_code, stdout) = run(&["decycle", "cycles"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    let report = serde_json::from_str::<serde_json::Value>(&stdout).unwrap();
+    assert_eq!(report["errors"], serde_json::json!([]));
+
+    let (exit_code, stdout) = run(&["decycle", "cut"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    let cut = serde_json::from_str::<serde_json::Value>(&stdout).unwrap();
+    assert_eq!(cut["cycles"], 1);
+    assert_eq!(cut["cycleSearch"], "maxLength");
+
+    let (exit_code, stdout) = run(&["decycle", "components", "--format", "text"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert!(stdout.ends_with("Found 1 component with cycles\n"));
+
+    let (exit_code, _) = run(&["decycle", "cycles", "--config", "missing.json"], &cwd);
+    assert_eq!(exit_code, ExitCode::from(2));
+
+    std::fs::write(cwd.join("invalid.json"), r#"{ "limit": {} }"#).unwrap();
+    let (exit_code, _) = run(&["decycle", "cycles", "--config", "invalid.json"], &cwd);
+    assert_eq!(exit_code, ExitCode::from(2));
+}
+
+#[test]
+fn test_ignore_comments() {
+    let cwd = fixture(
+        "ignore-comments",
+        &[
+            ("a.ts", "import './b';\nimport './c';"),
+            (
+                "b.ts",
+                "// decycle-ignore-next-line loaded lazily on purpose\nconst a = () => import('./a');",
+            ),
+            ("c.ts", "// decycle-ignore-next-line\nimport './a';\nimport './b';"),
+        ],
+    );
+    let (exit_code, stdout) = run(&["decycle", "cycles", "a.ts"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(
+        stdout,
+        "Found 0 cycles
+Ignored 2 imports with comments:
+  b.ts -> a.ts: loaded lazily on purpose
+    b.ts:2:24: const a = () => import('./a');
+  c.ts -> a.ts
+    c.ts:2:8: import './a';
+"
+    );
+
+    let (exit_code, stdout) = run(&["decycle", "check", "a.ts"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(stdout, "");
+
+    // legacy.ts is only imported by an ignored import, so its error isn't reported
+    let (exit_code, stdout) = run(
+        &["decycle", "cycles", "a.ts", "--format", "json"],
+        &fixture(
+            "ignore-comments-unreachable",
+            &[
+                ("a.ts", "// decycle-ignore-next-line\nimport './legacy';"),
+                ("legacy.ts", "import './missing';"),
+            ],
+        ),
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    let report: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(report["stats"]["files"], 1);
+    assert_eq!(report["errors"], serde_json::json!([]));
+    assert_eq!(report["ignoredImports"][0]["target"], "legacy.ts");
+}
+
+#[test]
+fn test_ignore_dynamic_imports() {
+    let cwd = fixture(
+        "ignore-dynamic-imports",
+        &[
+            ("a.ts", "import './b';\nimport './c';"),
+            ("b.ts", "export const load = () => import('./a');"),
+            ("c.ts", "import './a';\nconst lazy = () => import('./a');"),
+        ],
+    );
+    let (exit_code, stdout) = run(&["decycle", "cycles", "a.ts"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert!(stdout.ends_with("Found 2 cycles\n"));
+
+    let (exit_code, stdout) = run(
+        &["decycle", "cycles", "a.ts", "--ignore-dynamic-imports"],
+        &cwd,
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(
+        stdout,
+        "a.ts -> c.ts -> a.ts
+    a.ts:2:8: import './c';
+    c.ts:1:8: import './a';
+    c.ts:2:27: const lazy = () => import('./a');
+Found 1 cycle
+"
+    );
+}
+
+#[test]
+fn test_runtime_only() {
+    let cwd = fixture(
+        "runtime-only",
+        &[
+            (
+                "a.ts",
+                "import { f } from './b';\nimport { g } from './c';\nexport const x = 1;",
+            ),
+            (
+                "b.ts",
+                "import { x } from './a';\nexport function f() { return x; }",
+            ),
+            ("c.ts", "import { x } from './a';\nexport const g = x + 1;"),
+        ],
+    );
+    let (exit_code, stdout) = run(&["decycle", "cycles", "a.ts"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert!(stdout.ends_with("Found 2 cycles\n"));
+
+    let (exit_code, stdout) = run(&["decycle", "cycles", "a.ts", "--runtime-only"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(
+        stdout,
+        "a.ts -> c.ts -> a.ts
+    a.ts:2:19: import { g } from './c';
+    c.ts:1:19: import { x } from './a';
+Found 1 cycle
+"
+    );
+
+    // The maximum count applies to the cycles that can fail at runtime, even when shorter ones
+    // that can't are found first
+    let (exit_code, stdout) = run(
+        &[
+            "decycle",
+            "cycles",
+            "a.ts",
+            "--runtime-only",
+            "--max-cycles",
+            "1",
+        ],
+        &fixture(
+            "runtime-only-max-cycles",
+            &[
+                ("a.ts", "import './b';\nimport './c';\nexport const x = 1;"),
+                ("b.ts", "import './a';"),
+                ("c.ts", "import './d';"),
+                ("d.ts", "import { x } from './a';\nexport const y = x;"),
+            ],
+        ),
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(
+        stdout,
+        "a.ts -> c.ts -> d.ts -> a.ts
+    a.ts:2:8: import './c';
+    c.ts:1:8: import './d';
+    d.ts:1:19: import { x } from './a';
+Found 1 cycle
+"
+    );
+
+    let (exit_code, _) = run(&["decycle", "snapshot", "a.ts", "--runtime-only"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    let snapshot: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(cwd.join("cyclepath-snapshot.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(snapshot["runtimeOnly"], true);
+    assert_eq!(snapshot["edges"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_trace_barrels() {
+    let cwd = fixture(
+        "trace-barrels",
+        &[
+            (
+                "index.ts",
+                "export * from './a';\nexport { b as renamed } from './b';\nexport * as ns from './c';",
+            ),
+            ("a.ts", "import { renamed } from './index';\nexport const a = renamed;"),
+            ("b.ts", "import { a } from './index';\nexport const b = a;"),
+            ("c.ts", "import { a } from './index';\nexport const c = 1;"),
+        ],
+    );
+    let (exit_code, stdout) = run(&["decycle", "cycles", "index.ts"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert!(stdout.ends_with("Found 3 cycles\n"));
+
+    let (exit_code, stdout) = run(&["decycle", "cycles", "index.ts", "--trace-barrels"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(
+        stdout,
+        "a.ts -> b.ts -> a.ts
+    a.ts:1:25: import { renamed } from './index';
+    b.ts:1:19: import { a } from './index';
+Found 1 cycle
+"
+    );
+
+    // Ignore rules apply to the traced imports
+    std::fs::write(
+        cwd.join("ignore.json"),
+        r#"{ "traceBarrels": true, "ignore": [{ "source": "b.ts", "target": "a.ts" }] }"#,
+    )
+    .unwrap();
+    let (exit_code, stdout) = run(
+        &["decycle", "cycles", "index.ts", "--config", "ignore.json"],
+        &cwd,
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(stdout, "Found 0 cycles\n");
+    // Imported bindings exported by name are followed like re-exports
+    let cwd = fixture(
+        "trace-barrels-imports",
+        &[
+            (
+                "index.ts",
+                "import { a } from './a';\nimport { b } from './b';\nexport { a, b };",
+            ),
+            ("a.ts", "import { b } from './index';\nexport const a = b;"),
+            ("b.ts", "import { a } from './index';\nexport const b = a;"),
+        ],
+    );
+    let (exit_code, stdout) = run(&["decycle", "cycles", "index.ts", "--trace-barrels"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(
+        stdout,
+        "a.ts -> b.ts -> a.ts
+    a.ts:1:19: import { b } from './index';
+    b.ts:1:19: import { a } from './index';
+Found 1 cycle
+"
+    );
+}
+
+#[test]
+fn test_import_kinds() {
+    let cwd = fixture(
+        "import-kinds",
+        &[
+            (
+                "a.ts",
+                "import type { B } from './b';\nimport { c } from './c';\nexport type A = B;\nexport const a = c;",
+            ),
+            ("b.ts", "import type { A } from './a';\nexport type B = A;"),
+            ("c.ts", "import { a } from './a';\nexport const c = 1;"),
+        ],
+    );
+    let (exit_code, stdout) = run(&["decycle", "cycles", "a.ts"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert!(stdout.starts_with("a.ts -> c.ts -> a.ts\n"));
+    assert!(stdout.ends_with("Found 1 cycle\n"));
+
+    let (exit_code, stdout) = run(
+        &["decycle", "cycles", "a.ts", "--import-kinds", "type"],
+        &cwd,
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(
+        stdout,
+        "a.ts -> b.ts -> a.ts
+    a.ts:1:24: import type { B } from './b';
+    b.ts:1:24: import type { A } from './a';
+Found 1 cycle
+"
+    );
+
+    let (exit_code, stdout) = run(
+        &["decycle", "cycles", "a.ts", "--import-kinds", "both"],
+        &cwd,
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert!(stdout.ends_with("Found 2 cycles\n"));
+
+    let (exit_code, _) = run(
+        &["decycle", "snapshot", "a.ts", "--import-kinds", "type"],
+        &cwd,
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    let snapshot: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(cwd.join("cyclepath-snapshot.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(snapshot["importKinds"], "type");
+    assert_eq!(snapshot["edges"].as_array().unwrap().len(), 2);
+
+    // Imports with only inline `type` specifiers are type imports
+    let cwd = fixture(
+        "import-kinds-inline",
+        &[
+            (
+                "a.ts",
+                "import { type B } from './b';\nimport { c, type C } from './c';\nexport type A = B | C;\nexport const a = c;",
+            ),
+            ("b.ts", "import type { A } from './a';\nexport type B = A;"),
+            ("c.ts", "export type C = 1;\nexport const c = 1;"),
+        ],
+    );
+    let (exit_code, stdout) = run(&["decycle", "cycles", "a.ts"], &cwd);
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(stdout, "Found 0 cycles\n");
+
+    let (exit_code, stdout) = run(
+        &["decycle", "cycles", "a.ts", "--import-kinds", "type"],
+        &cwd,
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(
+        stdout,
+        "a.ts -> b.ts -> a.ts
+    a.ts:1:24: import { type B } from './b';
+    b.ts:1:24: import type { A } from './a';
+Found 1 cycle
+"
+    );
+
+    // Type imports resolve to declaration files
+    let cwd = fixture(
+        "import-kinds-declarations",
+        &[
+            (
+                "a.ts",
+                "import type { T } from './types';\nexport type A = T;",
+            ),
+            (
+                "types.d.ts",
+                "import type { A } from './a';\nexport type T = A[];",
+            ),
+        ],
+    );
+    let (exit_code, stdout) = run(
+        &["decycle", "cycles", "a.ts", "--import-kinds", "type"],
+        &cwd,
+    );
+    assert_eq!(exit_code, ExitCode::SUCCESS);
+    assert_eq!(
+        stdout,
+        "a.ts -> types.d.ts -> a.ts
+    a.ts:1:24: import type { T } from './types';
+    types.d.ts:1:24: import type { A } from './a';
+Found 1 cycle
+"
+    );
+}